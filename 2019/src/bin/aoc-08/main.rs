@@ -1,5 +1,6 @@
 use std::fs;
 use std::env;
+use aoc::ocr::recognise;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -28,20 +29,19 @@ fn main() {
 
     // Part 2
     {
-        println!("part 2:");
-
-        layers.iter()
+        let image: Vec<u32> = layers.iter()
             .fold(vec![2;width*height], |acc: Vec<u32>, l: &Vec<u32>| {
                 acc.iter()
                     .zip(l)
                     .map(|p| if *p.0 == 2 {*p.1} else {*p.0})
                     .collect()
-            })
-            [..]
-            .chunks(width)
-            .for_each(|row| {
-                println!("{}", row.iter().map(|c: &u32| if *c == 1 {"█"} else {" "}).collect::<String>());
             });
+
+        let pixels = image.iter()
+            .enumerate()
+            .filter(|(_, c)| **c == 1)
+            .map(|(i, _)| ((i % width) as i64, (i / width) as i64));
+        println!("part 2: {}", recognise(pixels).unwrap());
     }
 }
 
@@ -4,6 +4,7 @@ use intcode::VirtualMachine;
 use std::sync::mpsc;
 use std::thread;
 use std::collections::HashMap;
+use aoc::ocr::recognise;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -26,29 +27,16 @@ fn main() {
         let mut map: HashMap<(i64, i64), i64> = HashMap::new();
         map.insert((0, 0), 1);
         paint_map(&mut map, &code);
-        println!("part 2:");
-        print_map(&map);
+        println!("part 2: {}", read_map(&map));
     }
 }
 
-fn print_map(map: &HashMap<(i64, i64), i64>)
+fn read_map(map: &HashMap<(i64, i64), i64>) -> String
 {
-    let min_x = (map.iter().min_by_key(|((x,_y), _)| x).unwrap().0).0;
-    let max_x = (map.iter().max_by_key(|((x,_y), _)| x).unwrap().0).0;
-    let min_y = (map.iter().min_by_key(|((_x,y), _)| y).unwrap().0).1;
-    let max_y = (map.iter().max_by_key(|((_x,y), _)| y).unwrap().0).1;
-
-    for y in min_y..(max_y+1) {
-        for x in min_x..(max_x+1) {
-            let current_colour = map.get(&(x, y)).unwrap_or(&0);
-            if *current_colour == 1 {
-                print!("█");
-            } else {
-                print!(" ");
-            }
-        }
-        println!();
-    }
+    let white = map.iter()
+        .filter(|(_, colour)| **colour == 1)
+        .map(|(pos, _)| *pos);
+    recognise(white).unwrap()
 }
 
 fn paint_map(map: &mut HashMap<(i64, i64), i64>, code: &Vec<i64>) {
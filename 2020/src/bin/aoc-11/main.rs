@@ -8,70 +8,48 @@ use std::convert::{TryInto,TryFrom};
 use std::num::TryFromIntError;
 use core::str::FromStr;
 use std::collections::VecDeque;
+use aoc::automaton::{Automaton, Dense};
+use aoc::grid::Grid2D;
+use aoc::point::Point2;
 
 #[macro_use] extern crate scan_fmt;
 
-type ChairMap = Vec<Vec<char>>;
+type ChairMap = Grid2D<char>;
 
 fn parse_input(input: &str) -> ChairMap {
-    input
-        .lines()
-        .map(|line| line.chars().collect())
-        .collect()
+    input.parse().unwrap()
 }
 
 fn count_occupied(seats: &ChairMap) -> usize {
-    seats.iter()
-        .map(|line| line.iter().filter(|&&c| c == '#').count())
-        .sum()
-}
-
-fn get_seat(seats: &ChairMap, y: &i64, x: &i64) -> Option<char> {
-    seats.get(usize::try_from(*y).ok()?)?.get(usize::try_from(*x).ok()?).cloned()
+    seats.values().filter(|&&c| c == '#').count()
 }
 
-fn get_adjacent(seats: &ChairMap, y: &usize, x: &usize, use_los: &bool) -> usize {
-    [
-        (-1,-1),(-1, 0),(-1, 1),
-        ( 0, 1),        ( 1, 1),
-        ( 1, 0),( 1,-1),( 0,-1),
-    ].iter()
-        .map(|(yd, xd)| {
-            let mut yp: i64 = (*y).try_into().unwrap();
-            let mut xp: i64 = (*x).try_into().unwrap();
-            loop {
-                yp += yd;
-                xp += xd;
-                match get_seat(&seats, &yp, &xp) {
-                    Some('.') => if *use_los {continue;} else {return 0},
-                    Some('#') => return 1,
-                    _ => return 0,
-                }
-            }
-        })
-        .sum()
+fn get_adjacent(seats: &ChairMap, pos: (usize, usize), use_los: &bool) -> Vec<(usize, usize)> {
+    if !*use_los {
+        return seats.neighbours_8(pos).collect();
+    }
+    Point2::all_directions()
+        .into_iter()
+        .filter_map(|direction| seats.ray(pos, direction.into()).find(|p| seats[*p] != '.'))
+        .collect()
 }
 
-fn run_game(seats: &ChairMap, use_los: &bool, overcrowding: &usize) -> Result<ChairMap, TryFromIntError> {
-    let mut current = seats.clone();
-    loop {
-        let mut next = current.clone();
-        for (y, row) in current.iter().enumerate() {
-            for (x, seat) in row.iter().enumerate() {
-                let adjacent = get_adjacent(&current, &y, &x, &use_los);
-                match seat {
-                    'L' => if adjacent == 0 {next[y][x] = '#'},
-                    '#' => if adjacent >= *overcrowding {next[y][x] = 'L'},
-                    '.' => {},
-                    _ => panic!("Unknown character: {}", seat),
-                }
+fn run_game(seats: &ChairMap, use_los: &bool, overcrowding: &usize) -> ChairMap {
+    let mut game = Dense::new(
+        seats.clone(),
+        |seats, pos| get_adjacent(seats, pos, use_los),
+        |seat, adjacent| {
+            let occupied = adjacent.iter().filter(|&&&c| c == '#').count();
+            match seat {
+                'L' if occupied == 0 => '#',
+                '#' if occupied >= *overcrowding => 'L',
+                'L' | '#' | '.' => *seat,
+                _ => panic!("Unknown character: {}", seat),
             }
-        }
-        if next == current {
-            return Ok(next);
-        }
-        current = next;
-    }
+        },
+    );
+    game.run_to_fixed_point();
+    game.into_grid()
 }
 
 fn main() {
@@ -83,10 +61,10 @@ fn main() {
     let seats = parse_input(&input);
 
     let setup_time = SystemTime::now();
-    let new_seats = run_game(&seats, &false, &4).unwrap();
+    let new_seats = run_game(&seats, &false, &4);
     let part_1_ans = count_occupied(&new_seats);
     let part_1_time = SystemTime::now();
-    let new_seats_2 = run_game(&seats, &true, &5).unwrap();
+    let new_seats_2 = run_game(&seats, &true, &5);
     let part_2_ans = count_occupied(&new_seats_2);
     let part_2_time = SystemTime::now();
 
@@ -123,14 +101,14 @@ L.LLLLL.LL")
     fn example1a() {
         let seats = parse_input(&example1());
         assert_eq!(count_occupied(&seats), 0);
-        let new_seats = run_game(&seats, &false, &4).unwrap();
+        let new_seats = run_game(&seats, &false, &4);
         assert_eq!(count_occupied(&new_seats), 37);
     }
 
     #[test]
     fn example1b() {
         let seats = parse_input(&example1());
-        let new_seats = run_game(&seats, &true, &5).unwrap();
+        let new_seats = run_game(&seats, &true, &5);
         assert_eq!(count_occupied(&new_seats), 26);
     }
 }
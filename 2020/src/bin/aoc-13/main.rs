@@ -9,6 +9,7 @@ use std::num::TryFromIntError;
 use core::str::FromStr;
 use std::collections::VecDeque;
 use num::abs;
+use aoc::number_theory::crt;
 
 #[macro_use] extern crate scan_fmt;
 
@@ -29,36 +30,13 @@ fn find_next_departure(now: &usize, departures: &Vec<Option<usize>>) -> (usize,
     (next, next - (now % next))
 }
 
-fn find_period(a: usize, b: &usize, difference: &usize) {
-}
-
 fn find_sequential_departures(departures: &Vec<Option<usize>>) -> usize {
-    //println!("{:?}", departures);
-    let mut period = departures[0].unwrap();
-    let mut time = 0;
-
-    for (position, departure) in departures.iter().enumerate().skip(1) {
-        if departure.is_none() {
-            continue;
-        }
-        println!("position: {}, departure: {:?}, period: {}", position, departure, period);
-
-        let mut first_result = None;
-        loop {
-            if (time + position) % departure.unwrap() == 0 {
-                if first_result.is_some() {
-                    period = time - first_result.unwrap();
-                    time = first_result.unwrap();
-                    break;
-                }
-                else {
-                    first_result = Some(time);
-                }
-            }
-            time += period;
-        }
-    }
-    time
+    let congruences: Vec<(i64, i64)> = departures.iter()
+        .enumerate()
+        .filter_map(|(position, departure)| departure.map(|d| (-(position as i64), d as i64)))
+        .collect();
+    let (time, _) = crt(&congruences).expect("Departures can never line up");
+    time as usize
 }
 
 fn main() {
@@ -12,106 +12,25 @@ use core::str::FromStr;
 use std::collections::VecDeque;
 use num::abs;
 use rand::{thread_rng, Rng};
+use aoc::grammar::Grammar;
+use aoc::parse::{Input, ParseError};
 
-type RuleMap = HashMap<usize, Rule>;
-
-enum Pattern {
-    Character {
-        character: char,
-    },
-    Rules {
-        rule_nums: Vec<usize>,
-    },
-}
-
-struct Rule {
-    number: usize,
-    patterns: Vec<Pattern>,
-}
-
-impl Rule {
-    fn match_lengths(&self, rules: &RuleMap, input: &str) -> Vec<usize> {
-        self.patterns.iter().map(|pattern| {
-            match pattern {
-                Pattern::Character { character } => {
-                    if input.chars().nth(0) == Some(*character) {
-                        return vec![1];
-                    }
-                    else {
-                        return vec![];
-                    }
-                },
-                Pattern::Rules { rule_nums } => {
-                    let mut current_match_lengths = vec![0];
-                    for rule_num in rule_nums {
-                        let mut next_match_lengths = vec![];
-                        for match_length in current_match_lengths {
-                            let rule_lengths = rules[rule_num].match_lengths(&rules, &input[match_length..]);
-                            for length in rule_lengths {
-                                next_match_lengths.push(length + match_length);
-                            }
-                        }
-                        current_match_lengths = next_match_lengths;
-                    }
-                    return current_match_lengths
-                },
-            }
-        })
-        .flatten()
-        .collect()
-    }
-}
-
-impl FromStr for Rule {
-    type Err = std::string::ParseError;
-
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = input.split(": ").collect();
-        let number = usize::from_str_radix(parts[0], 10).unwrap();
-
-        let pattern_strs = parts[1].split("|");
-        let mut patterns = Vec::new();
-        for pattern_str in pattern_strs {
-            let p = pattern_str.trim();
-            if p.chars().nth(0).unwrap() == '"' {
-                patterns.push(Pattern::Character{
-                    character: p.chars().nth(1).unwrap(),
-                });
-            }
-            else {
-                let rule_nums = p.split(" ")
-                    .map(|n| usize::from_str_radix(n, 10).unwrap())
-                    .collect_vec();
-                patterns.push(Pattern::Rules{
-                    rule_nums
-                });
-            }
-        }
-
-        Ok(Rule {
-            number,
-            patterns,
-        })
+fn parse_input(input: &str) -> Result<(Grammar, Vec<&str>), ParseError> {
+    let sections = Input::new(input).sections();
+    if sections.len() != 2 {
+        return Err(Input::new(input).error("Expected rules and messages separated by a blank line"));
     }
-}
-
-fn parse_input(input: &str) -> (RuleMap, Vec<String>) {
-    let inputs = input.split("\n\n").collect_vec();
-    let messages = inputs[1].lines()
-        .map(|line| line.to_string())
+    let grammar = Grammar::parse(sections[0])?;
+    let messages = sections[1].rest().lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
         .collect_vec();
-    let rules: RuleMap = inputs[0].lines()
-        .map(|line| Rule::from_str(&line).unwrap())
-        .map(|rule| (rule.number, rule))
-        .collect();
-    (rules, messages)
+    Ok((grammar, messages))
 }
 
-fn find_matching(rules: &RuleMap, rule_num: &usize, messages: &Vec<String>) -> Vec<String> {
-    messages.iter()
-        .filter(|message| rules[rule_num].match_lengths(&rules, &message).iter().any(|len| len == &message.len()))
-        .cloned()
-        .collect()
+// The rules that loop back on themselves in part 2
+fn loop_rules(grammar: &mut Grammar) {
+    grammar.add_rules(Input::new("8: 42 | 42 8\n11: 42 31 | 42 11 31")).unwrap();
 }
 
 fn main() {
@@ -122,26 +41,12 @@ fn main() {
 
     let setup_time = SystemTime::now();
 
-    let (mut rules, messages) = parse_input(&input);
-    let part_1_ans = find_matching(&rules, &0, &messages).len();
+    let (mut grammar, messages) = parse_input(&input).unwrap_or_else(|e| panic!("Bad input: {}", e));
+    let part_1_ans = grammar.count_matches(0, messages.iter().copied());
     let part_1_time = SystemTime::now();
 
-
-    rules.insert(8, Rule{
-        number: 8,
-        patterns: vec![
-            Pattern::Rules{rule_nums: vec![42]},
-            Pattern::Rules{rule_nums: vec![42, 8]},
-        ],
-    });
-    rules.insert(11, Rule{
-        number: 11,
-        patterns: vec![
-            Pattern::Rules{rule_nums: vec![42, 31]},
-            Pattern::Rules{rule_nums: vec![42, 11, 31]},
-        ],
-    });
-    let part_2_ans = find_matching(&rules, &0, &messages).len();
+    loop_rules(&mut grammar);
+    let part_2_ans = grammar.count_matches(0, messages.iter().copied());
     let part_2_time = SystemTime::now();
 
     println!("Part 1: {:?}", part_1_ans);
@@ -156,10 +61,7 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::parse_input;
-    use super::find_matching;
-    use super::Rule;
-    use super::Pattern;
-    use super::RuleMap;
+    use super::loop_rules;
 
     fn example0() -> String {
         String::from(
@@ -172,8 +74,9 @@ b"
 
     #[test]
     fn example0a() {
-        let (rules, messages) = parse_input(&example0());
-        assert_eq!(find_matching(&rules, &0, &messages).len(), 1);
+        let input = example0();
+        let (grammar, messages) = parse_input(&input).unwrap();
+        assert_eq!(grammar.count_matches(0, messages), 1);
     }
 
     fn example1() -> String {
@@ -195,8 +98,11 @@ aaaabbb"
 
     #[test]
     fn example1a() {
-        let (rules, messages) = parse_input(&example1());
-        assert_eq!(find_matching(&rules, &0, &messages).len(), 2);
+        let input = example1();
+        let (grammar, messages) = parse_input(&input).unwrap();
+        assert_eq!(grammar.count_matches(0, messages.iter().copied()), 2);
+        assert_eq!(grammar.derive(0, "ababbb").unwrap().to_string(), "0(4(a) 1(3(5(b) 4(a)) 2(5(b) 5(b))) 5(b))");
+        assert_eq!(grammar.derive(0, "bababa").unwrap_err().to_string(), "at position 0: expected 'a', found 'b'");
     }
 
     fn example2() -> String {
@@ -253,21 +159,10 @@ aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba"
 
     #[test]
     fn example2a() {
-        let (mut rules, messages) = parse_input(&example2());
-        rules.insert(8, Rule{
-            number: 8,
-            patterns: vec![
-                Pattern::Rules{rule_nums: vec![42]},
-                Pattern::Rules{rule_nums: vec![42, 8]},
-            ],
-        });
-        rules.insert(11, Rule{
-            number: 11,
-            patterns: vec![
-                Pattern::Rules{rule_nums: vec![42, 31]},
-                Pattern::Rules{rule_nums: vec![42, 11, 31]},
-            ],
-        });
-        assert_eq!(find_matching(&rules, &0, &messages).len(), 12);
+        let input = example2();
+        let (mut grammar, messages) = parse_input(&input).unwrap();
+        assert_eq!(grammar.count_matches(0, messages.iter().copied()), 3);
+        loop_rules(&mut grammar);
+        assert_eq!(grammar.count_matches(0, messages.iter().copied()), 12);
     }
 }
@@ -12,192 +12,50 @@ use core::str::FromStr;
 use std::collections::VecDeque;
 use num::abs;
 use rand::{thread_rng, Rng};
+use aoc::ring::{NodeId, Ring};
 
-#[derive(Debug, PartialEq, Clone)]
-struct ListElement {
-    id: usize,
-    next: usize,
-    previous: usize,
-    value: usize,
-    dest: usize,
-}
-
-#[derive(Debug, PartialEq, Clone)]
-struct List {
-    next_id: usize,
-    elements: HashMap<usize, ListElement>,
-}
-
-impl List {
-    fn new() -> Self {
-        List {
-            next_id: 0,
-            elements: HashMap::new(),
-        }
-    }
-
-    fn push(&mut self, value: usize) -> usize {
-        if self.elements.len() == 0 {
-            let new_element;
-            new_element = ListElement{
-                id: self.next_id,
-                next: self.next_id,
-                previous: self.next_id,
-                value: value,
-                dest: self.next_id,
-            };
-            self.elements.insert(new_element.id, new_element);
-            self.next_id += 1;
-        } else {
-            self.insert_after(self.next_id - 1, value);
-        }
-        return self.next_id - 1;
-    }
-
-    fn insert_element_after(&mut self, id: usize, mut elem: ListElement) {
-        let previous_id;
-        let next_id;
-        {
-            let previous = self.elements.get(&id).unwrap();
-            previous_id = previous.id;
-            next_id = previous.next;
-        }
-        elem.next = next_id;
-        elem.previous = previous_id;
-        self.elements.get_mut(&previous_id).unwrap().next = elem.id;
-        self.elements.get_mut(&next_id).unwrap().previous = elem.id;
-        self.elements.insert(elem.id, elem);
-    }
-
-    fn insert_after(&mut self, id: usize, value: usize) -> usize {
-        let new_element;
-        let new_id = self.next_id;
-        self.next_id += 1;
-
-        let previous_id;
-        let next_id;
-        {
-            let previous = self.elements.get(&id).unwrap();
-            previous_id = previous.id;
-            next_id = previous.next;
-        }
-
-        new_element = ListElement{
-            id: new_id,
-            next: next_id,
-            previous: previous_id,
-            value: value,
-            dest: previous_id,
-        };
-        self.elements.insert(new_id, new_element);
-        {
-            let mut previous = self.elements.get_mut(&previous_id).unwrap();
-            previous.next = new_id;
-        }
-        {
-            let mut next = self.elements.get_mut(&next_id).unwrap();
-            next.previous = new_id;
-        }
-        return new_id;
+fn parse_cups(input: &str, max: &usize) -> Ring<usize> {
+    let mut cups = Ring::with_capacity(*max);
+    for c in input.trim().chars() {
+        cups.push(c.to_digit(10).unwrap() as usize);
     }
-
-    fn remove(&mut self, id: usize) -> ListElement {
-        let previous_id;
-        let next_id;
-        {
-            let current = self.elements.get(&id).unwrap().clone();
-            previous_id = current.previous;
-            next_id = current.next;
-        }
-        self.elements.get_mut(&previous_id).unwrap().next = next_id;
-        self.elements.get_mut(&next_id).unwrap().previous = previous_id;
-        return self.elements.remove(&id).unwrap();
-    }
-
-    fn get(&self, id: &usize) -> ListElement {
-        self.elements[id].clone()
-    }
-
-    fn find_value(&self, value: usize) -> ListElement {
-        self.elements
-            .values()
-            .find(|e| e.value == value).unwrap()
-            .clone()
-    }
-
-    fn from_string(input: &str, max: &usize) -> Self {
-        let mut result = List::new();
-        for c in input.trim().chars() {
-            result.push(usize::from_str_radix(&c.to_string(), 10).unwrap());
-        }
-
-        for i in result.elements.len()+1..=*max {
-            result.push(i);
-        }
-
-        for current_id in 0..result.next_id {
-            let current = result.elements.get(&current_id).unwrap();
-            let mut prev_value = current.value - 1;
-            if prev_value == 0 {
-                prev_value = *max;
-            }
-
-            let dest_id;
-            {
-                let mut dest = result.elements.get(&current_id).unwrap();
-                while dest.value != prev_value {
-                    dest = result.elements.get(&dest.previous).unwrap();
-                }
-                dest_id = dest.id;
-            }
-
-            {
-                let mut current = result.elements.get_mut(&current_id).unwrap();
-                current.dest = dest_id;
-            }
-        }
-
-        result
+    for i in cups.len()+1..=*max {
+        cups.push(i);
     }
+    cups
 }
 
-fn play_turn(deck: &mut List, current_id: &usize) -> usize {
-    let a = deck.remove(deck.get(current_id).next);
-    let b = deck.remove(deck.get(current_id).next);
-    let c = deck.remove(deck.get(current_id).next);
+fn play_turn(cups: &mut Ring<usize>, current_id: &NodeId) -> NodeId {
+    let picked = cups.remove_after(*current_id, 3);
+    let picked_values = [picked.first, cups.next(picked.first), picked.last]
+        .iter()
+        .map(|id| *cups.value(*id))
+        .collect_vec();
 
-    let mut dest_id = deck.get(current_id).dest;
+    let max = cups.len() + picked.len;
+    let mut dest = *cups.value(*current_id);
     loop {
-        match [&a, &b, &c].iter().find(|e| e.id == dest_id) {
-            Some(e) => dest_id = e.dest,
-            None => break,
+        dest = if dest == 1 { max } else { dest - 1 };
+        if !picked_values.contains(&dest) {
+            break;
         }
     }
-    deck.insert_element_after(dest_id, c);
-    deck.insert_element_after(dest_id, b);
-    deck.insert_element_after(dest_id, a);
-    return deck.get(current_id).next;
+    cups.splice_after(cups.find(&dest).unwrap(), picked);
+    cups.next(*current_id)
 }
 
-fn get_score(deck: &List) -> String {
-    let mut current = deck.find_value(1);
-    current = deck.get(&current.next);
-    let mut result = "".to_string();
-    while current.value != 1 {
-        result += &current.value.to_string();
-        current = deck.get(&current.next);
-    }
-    result
+fn get_score(cups: &Ring<usize>) -> String {
+    cups.iter_from(cups.find(&1).unwrap())
+        .skip(1)
+        .map(|cup| cup.to_string())
+        .collect()
 }
 
-fn get_part_2_score(deck: &List) -> usize {
-    let mut current = deck.find_value(1);
-    current = deck.get(&current.next);
-    let a = current.value;
-    current = deck.get(&current.next);
-    let b = current.value;
-    let result = a * b;
-    result
+fn get_part_2_score(cups: &Ring<usize>) -> usize {
+    cups.iter_from(cups.find(&1).unwrap())
+        .skip(1)
+        .take(2)
+        .product()
 }
 
 fn main() {
@@ -208,7 +66,7 @@ fn main() {
 
     let setup_time = SystemTime::now();
 
-    let mut deck = List::from_string(&input, &9);
+    let mut deck = parse_cups(&input, &9);
     let mut current_id = 0;
     for _ in 0..100 {
         current_id = play_turn(&mut deck, &current_id);
@@ -216,12 +74,9 @@ fn main() {
     let part_1_ans = get_score(&deck);
     let part_1_time = SystemTime::now();
 
-    deck = List::from_string(&input, &1_000_000);
+    deck = parse_cups(&input, &1_000_000);
     current_id = 0;
-    for i in 0..10_000_000 {
-        if i % 10_000 == 0 {
-            println!("loop {}", i);
-        }
+    for _ in 0..10_000_000 {
         current_id = play_turn(&mut deck, &current_id);
     }
     let part_2_ans = get_part_2_score(&deck);
@@ -241,7 +96,7 @@ mod tests {
     use super::play_turn;
     use super::get_score;
     use super::get_part_2_score;
-    use super::List;
+    use super::parse_cups;
 
     fn example1() -> String {
         String::from(
@@ -251,7 +106,7 @@ mod tests {
 
     #[test]
     fn example1a() {
-        let mut deck = List::from_string(&example1(), &9);
+        let mut deck = parse_cups(&example1(), &9);
         let mut current_id = 0;
         for _ in 0..10 {
             current_id = play_turn(&mut deck, &current_id);
@@ -261,12 +116,9 @@ mod tests {
 
     #[test]
     fn example1b() {
-        let mut deck = List::from_string(&example1(), &1_000_000);
+        let mut deck = parse_cups(&example1(), &1_000_000);
         let mut current_id = 0;
-        for i in 0..10_000_000 {
-            if i % 10_000 == 0 {
-                println!("loop {}", i);
-            }
+        for _ in 0..10_000_000 {
             current_id = play_turn(&mut deck, &current_id);
         }
         assert_eq!(get_part_2_score(&deck), 149245887792);
@@ -12,24 +12,25 @@ use core::str::FromStr;
 use std::collections::VecDeque;
 use num::abs;
 use rand::{thread_rng, Rng};
+use aoc::automaton::{Automaton, Sparse};
+use aoc::hex::{Hex, Orientation};
 
 #[derive(Debug, PartialEq, Clone)]
 struct HexGrid {
-    tiles: HashSet<(i64, i64)>,
+    tiles: HashSet<Hex>,
 }
 
 impl FromStr for HexGrid {
-    type Err = std::string::ParseError;
+    type Err = String;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
         let mut tiles = HashSet::new();
 
         for line in input.lines() {
-            let pos = follow_hex_directions(&line, (0, 0));
-            if tiles.contains(&pos) {
-                tiles.remove(&pos);
-            }
-            else {
+            let pos = Orientation::Pointy.parse_path(line)?
+                .into_iter()
+                .fold(Hex::default(), |pos, step| pos + step);
+            if !tiles.remove(&pos) {
                 tiles.insert(pos);
             }
         }
@@ -43,123 +44,20 @@ impl HexGrid {
         self.tiles.len()
     }
 
-    fn count_neighbours(&self, pos: &(i64, i64)) -> usize {
-        directions().iter()
-            .map(|dir| follow_hex_directions(dir, *pos))
-            .filter(|pos| self.tiles.contains(pos))
-            .count()
-    }
-
     fn print(&self) {
         println!("Grid:");
-        let min_x = self.tiles.iter().min_by_key(|pos| pos.0).unwrap().0;
-        let min_y = self.tiles.iter().min_by_key(|pos| pos.1).unwrap().1;
-        let max_x = self.tiles.iter().max_by_key(|pos| pos.0).unwrap().0;
-        let max_y = self.tiles.iter().max_by_key(|pos| pos.1).unwrap().1;
-
-        for y in min_y..=max_y {
-            if y % 2 != 0 {
-                print!(" ");
-            }
-            for x in min_x..=max_x {
-                if self.tiles.contains(&(x, y)) {
-                    print!("# ");
-                }
-                else {
-                    print!(". ");
-                }
-            }
-            println!("");
-        }
+        println!("{}", Orientation::Pointy.render(self.tiles.iter().map(|pos| (*pos, '#')), '.'));
     }
 
     fn do_turn(&mut self) {
-        let to_check: HashSet<(i64, i64)> = self.tiles.iter()
-            .cartesian_product(directions().iter())
-            .map(|(pos, dir)| follow_hex_directions(dir, *pos))
-            .collect::<HashSet<(i64, i64)>>()
-            .union(&self.tiles)
-            .cloned()
-            .collect();
-
-        let mut next_tiles = self.tiles.clone();
-        for pos in to_check {
-            let neighbours = self.count_neighbours(&pos);
-            if self.tiles.contains(&pos) {
-                if neighbours == 0 || neighbours > 2 {
-                    next_tiles.remove(&pos);
-                }
-            }
-            else {
-                if neighbours == 2 {
-                    next_tiles.insert(pos);
-                }
-            }
-        }
-
-        self.tiles = next_tiles;
-    }
-}
-
-fn directions() -> Vec<String> {
-    vec!["e", "se", "sw", "w", "nw", "ne"]
-        .iter()
-        .map(|s| s.to_string())
-        .collect()
-}
-
-fn follow_hex_directions(directions: &str, mut pos: (i64, i64)) -> (i64, i64) {
-    let mut index = 0;
-    while index < directions.len() {
-        if directions[index..].starts_with("e") {
-            pos = (pos.0 + 1, pos.1);
-            index += 1;
-        }
-        else if directions[index..].starts_with("se") {
-            if pos.1 % 2 == 0 {
-                pos = (pos.0, pos.1 - 1);
-            }
-            else {
-                pos = (pos.0 + 1, pos.1 - 1);
-            }
-            index += 2;
-        }
-        else if directions[index..].starts_with("sw") {
-            if pos.1 % 2 == 0 {
-                pos = (pos.0 - 1, pos.1 - 1);
-            }
-            else {
-                pos = (pos.0, pos.1 - 1);
-            }
-            index += 2;
-        }
-        else if directions[index..].starts_with("w") {
-            pos = (pos.0 - 1, pos.1);
-            index += 1;
-        }
-        else if directions[index..].starts_with("nw") {
-            if pos.1 % 2 == 0 {
-                pos = (pos.0 - 1, pos.1 + 1);
-            }
-            else {
-                pos = (pos.0, pos.1 + 1);
-            }
-            index += 2;
-        }
-        else if directions[index..].starts_with("ne") {
-            if pos.1 % 2 == 0 {
-                pos = (pos.0, pos.1 + 1);
-            }
-            else {
-                pos = (pos.0 + 1, pos.1 + 1);
-            }
-            index += 2;
-        }
-        else {
-            panic!("Unknown directions: {}", directions[index..].to_string());
-        }
+        let mut floor = Sparse::new(
+            self.tiles.drain(),
+            |pos: &Hex| pos.neighbours(),
+            |black, neighbours| neighbours == 2 || (black && neighbours == 1),
+        );
+        floor.step();
+        self.tiles = floor.into_live();
     }
-    pos
 }
 
 fn main() {
@@ -12,6 +12,7 @@ use core::str::FromStr;
 use std::collections::VecDeque;
 use num::abs;
 use rand::{thread_rng, Rng};
+use aoc::number_theory::{discrete_log, modpow};
 
 
 fn parse_input(input: &str) -> (u64, u64) {
@@ -21,19 +22,14 @@ fn parse_input(input: &str) -> (u64, u64) {
     }
 }
 
-fn apply_loop(val: &u64, subject: &u64) -> u64 {
-    let modulo = 20201227;
-    (val * subject) % modulo
+const MODULUS: i64 = 20201227;
+
+fn transform(subject: &u64, loops: &u64) -> u64 {
+    modpow(*subject as i64, *loops, MODULUS) as u64
 }
 
 fn find_loop_number(public_key: &u64) -> u64 {
-    let mut val = 1;
-    let mut loops = 0;
-    while val != *public_key {
-        val = apply_loop(&val, &7);
-        loops += 1
-    }
-    loops
+    discrete_log(7, *public_key as i64, MODULUS).expect("No loop number found") as u64
 }
 
 fn main() {
@@ -46,11 +42,7 @@ fn main() {
 
     let (card, door) = parse_input(&input);
     let door_loop = find_loop_number(&door);
-    let mut key = 1;
-    for _ in 0..door_loop {
-        key = apply_loop(&key, &card);
-    }
-    let part_1_ans = key;
+    let part_1_ans = transform(&card, &door_loop);
     let part_1_time = SystemTime::now();
 
     println!("Part 1: {:?}", part_1_ans);
@@ -64,7 +56,7 @@ fn main() {
 mod tests {
     use super::parse_input;
     use super::find_loop_number;
-    use super::apply_loop;
+    use super::transform;
 
     fn example1() -> String {
         String::from(
@@ -80,10 +72,7 @@ mod tests {
         let door_loop = find_loop_number(&door);
         assert_eq!(card_loop, 8);
         assert_eq!(door_loop, 11);
-        let mut key = 1;
-        for _ in 0..door_loop {
-            key = apply_loop(&key, &card);
-        }
-        assert_eq!(key, 14897079);
+        assert_eq!(transform(&card, &door_loop), 14897079);
+        assert_eq!(transform(&door, &card_loop), 14897079);
     }
 }
@@ -5,9 +5,8 @@ use std::time::SystemTime;
 use std::convert::TryInto;
 use std::convert::TryFrom;
 use std::collections::HashMap;
-use std::collections::HashSet;
 use std::cmp::min;
-use itertools::Itertools;
+use aoc::union_find::UnionFind;
 use std::ops::{Add, Sub};
 #[macro_use] extern crate scan_fmt;
 
@@ -58,24 +57,6 @@ fn has_lower_adjacent(map: &HashMap<Pos, i64>, pos: &Pos) -> bool {
         .any(|p| map.get(&(*pos + *p)).unwrap_or(&9) <= map.get(pos).unwrap())
 }
 
-fn get_basin(map: &HashMap<Pos, i64>, pos: &Pos, prev_basin: &HashSet<Pos>) -> HashSet<Pos> {
-    let mut basin = HashSet::new();
-    let val = map.get(pos).unwrap();
-    basin.insert(*pos);
-
-    for dir in directions() {
-        let next = *pos + dir;
-        if prev_basin.contains(&next) {continue}
-        if !map.contains_key(&next) {continue}
-        if map.get(&next).unwrap() <= val {continue}
-        if map.get(&next).unwrap() == &9 {continue}
-
-        basin = basin.union(&get_basin(&map, &next, &basin)).cloned().collect();
-    }
-
-    basin
-}
-
 fn part_1(map: &HashMap<Pos, i64>) -> i64 {
     map.iter()
         .filter(|(key, _val)| !has_lower_adjacent(&map, &key))
@@ -83,14 +64,23 @@ fn part_1(map: &HashMap<Pos, i64>) -> i64 {
         .sum()
 }
 
+// Every cell other than a 9 is in exactly one basin, so basins are just the connected regions
 fn part_2(map: &HashMap<Pos, i64>) -> usize {
-    map.iter()
-        .filter(|(key, _val)| !has_lower_adjacent(&map, &key))
-        .map(|(key, _val)| get_basin(&map, &key, &HashSet::new()).len())
-        .sorted()
-        .rev()
-        .take(3)
-        .fold(1, |acc,a| acc*a)
+    let positions: Vec<Pos> = map.keys().cloned().collect();
+    let index: HashMap<Pos, usize> = positions.iter().enumerate().map(|(i, pos)| (*pos, i)).collect();
+    let mut basins = UnionFind::new(positions.len());
+
+    for (i, pos) in positions.iter().enumerate() {
+        if map[pos] == 9 {continue}
+        for dir in directions() {
+            match index.get(&(*pos + dir)) {
+                Some(j) if map[&positions[*j]] != 9 => {basins.union(i, *j);},
+                _ => {},
+            }
+        }
+    }
+
+    basins.component_sizes().iter().take(3).product()
 }
 
 fn main() {
@@ -9,6 +9,7 @@ use std::collections::HashSet;
 use std::cmp::min;
 use itertools::Itertools;
 use std::ops::{Add};
+use aoc::ocr::recognise;
 #[macro_use] extern crate scan_fmt;
 
 #[derive(Debug, PartialEq, Clone, Eq, Hash, Copy)]
@@ -46,28 +47,6 @@ enum Fold {
     Vertical(i64),
 }
 
-fn print_map(map: &HashSet<Pos>) -> String
-{
-    let min_x = map.iter().min_by_key(|&pos| pos.x).unwrap().x;
-    let max_x = map.iter().max_by_key(|&pos| pos.x).unwrap().x;
-    let min_y = map.iter().min_by_key(|&pos| pos.y).unwrap().y;
-    let max_y = map.iter().max_by_key(|&pos| pos.y).unwrap().y;
-
-    let mut result = String::new();
-    for y in min_y..(max_y+1) {
-        for x in min_x..(max_x+1) {
-            if map.contains(&Pos{x, y}) {
-                result += "#";
-            } else {
-                result += ".";
-            }
-        }
-        result += "\n";
-    }
-    result
-}
-
-
 fn parse_input(input: &str) -> (HashSet<Pos>, Vec<Fold>) {
     let parts: Vec<&str> = input.split("\n\n").collect();
 
@@ -112,10 +91,13 @@ fn part_1(map: &HashSet<Pos>, folds: &Vec<Fold>) -> usize {
     result.len()
 }
 
+fn fold_all(map: &HashSet<Pos>, folds: &Vec<Fold>) -> HashSet<Pos> {
+    folds.iter()
+        .fold(map.clone(), |acc, fold| apply_fold(&acc, &fold))
+}
+
 fn part_2(map: &HashSet<Pos>, folds: &Vec<Fold>) -> String {
-    let result = folds.iter()
-        .fold(map.clone(), |acc, fold| apply_fold(&acc, &fold));
-    print_map(&result)
+    recognise(fold_all(map, folds).iter().map(|pos| (pos.x, pos.y))).unwrap()
 }
 
 fn main() {
@@ -133,7 +115,7 @@ fn main() {
     let part_2_time = SystemTime::now();
 
     println!("Part 1: {:?}", part_1_ans);
-    println!("Part 2:\n{}", part_2_ans);
+    println!("Part 2: {}", part_2_ans);
     println!("\nTime beakdowns:\n\nSetup: {:?}\nPart 1: {:?}\nPart 2: {:?}\nTotal: {:?}",
         setup_time.duration_since(start_time).unwrap(),
         part_1_time.duration_since(setup_time).unwrap(),
@@ -145,7 +127,21 @@ fn main() {
 mod tests {
     use super::parse_input;
     use super::part_1;
+    use super::fold_all;
     use super::part_2;
+    use super::Pos;
+    use std::collections::HashSet;
+
+    fn dots(rendered: &str) -> HashSet<Pos> {
+        rendered.lines()
+            .enumerate()
+            .flat_map(|(y, line)| line.chars()
+                .enumerate()
+                .filter(|(_, c)| *c == '#')
+                .map(move |(x, _)| Pos{x: x as i64, y: y as i64}))
+            .collect()
+    }
+
     #[test]
     fn example1() {
         let input =
@@ -172,12 +168,36 @@ fold along y=7
 fold along x=5";
         let (map, folds) = parse_input(input);
         assert_eq!(part_1(&map, &folds), 17);
-        assert_eq!(part_2(&map, &folds),
+        assert_eq!(fold_all(&map, &folds), dots(
 "#####
 #...#
 #...#
 #...#
-#####
-");
+#####"));
+    }
+
+    #[test]
+    fn letters() {
+        let letters = dots(
+"#....####
+#....#...
+#....###.
+#....#...
+#....#...
+####.#...");
+        // Scatter the letters over both folds so only folding brings them back together
+        let mut input = letters.iter()
+            .enumerate()
+            .map(|(i, pos)| {
+                let x = if i % 2 == 0 { pos.x } else { 20 - pos.x };
+                let y = if i % 3 == 0 { pos.y } else { 12 - pos.y };
+                format!("{},{}", x, y)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        input += "\n\nfold along y=6\nfold along x=10";
+        let (map, folds) = parse_input(&input);
+        assert_eq!(fold_all(&map, &folds), letters);
+        assert_eq!(part_2(&map, &folds), "LF");
     }
 }
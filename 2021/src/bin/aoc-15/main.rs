@@ -9,6 +9,7 @@ use std::collections::HashSet;
 use std::collections::BTreeSet;
 use std::cmp::min;
 use itertools::Itertools;
+use aoc::search::dijkstra;
 use std::ops::{Add};
 #[macro_use] extern crate scan_fmt;
 
@@ -61,37 +62,17 @@ fn find_path(map: &HashMap<Pos, i64>) -> i64 {
         .max_by_key(|pos| pos.x + pos.y)
         .unwrap();
 
-    let mut to_check = BTreeSet::new();
-    let mut best_routes = HashMap::new();
-
-    to_check.insert((0, start));
-    best_routes.insert(start, 0);
-
-    while !to_check.is_empty() {
-        let current_pos = to_check.take(&to_check.iter().min().unwrap().clone()).unwrap().1;
-        let current_score = best_routes.get(&current_pos).unwrap().clone();
-
-        if current_pos == *finish {
-            return current_score;
-        }
-
-        for direction in directions() {
-            let next_pos = current_pos + direction;
-            if map.get(&next_pos).is_none() {
-                continue;
-            }
-
-            let next_score = map.get(&next_pos).unwrap() + current_score;
-            if best_routes.contains_key(&next_pos) && best_routes.get(&next_pos).unwrap() <= &next_score {
-                continue;
-            }
-
-            to_check.insert((next_score, next_pos));
-            best_routes.insert(next_pos, next_score);
-        }
-    }
-
-    panic!("Could not find exit");
+    let successors = |current_pos: &Pos| {
+        directions()
+            .into_iter()
+            .map(|direction| *current_pos + direction)
+            .filter_map(|next_pos| map.get(&next_pos).map(|risk| (next_pos, *risk)))
+            .collect()
+    };
+
+    dijkstra(start, successors, |pos| pos == finish)
+        .expect("Could not find exit")
+        .cost
 }
 
 fn part_1(map: &HashMap<Pos, i64>) -> i64 {
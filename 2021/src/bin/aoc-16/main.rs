@@ -1,4 +1,5 @@
 use std::fs;
+use std::fmt;
 use std::env;
 use std::str::FromStr;
 use std::time::SystemTime;
@@ -15,10 +16,17 @@ use hex::FromHex;
 use bit_vec::BitVec;
 #[macro_use] extern crate scan_fmt;
 
+// How an operator says where its subpackets end
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash)]
+enum LengthType {
+    Bits,
+    Count,
+}
+
 #[derive(Debug, PartialEq, Clone, Eq, Hash)]
 enum Packet {
     Literal{version: usize, packet_type: usize, value: usize},
-    Operator{version: usize, packet_type: usize, subpackets: Vec<Packet>},
+    Operator{version: usize, packet_type: usize, length_type: LengthType, subpackets: Vec<Packet>},
 }
 
 impl Packet {
@@ -31,11 +39,11 @@ impl Packet {
                 match bitstream.next().unwrap() {
                     false => {
                         let bits_to_read = bits_to_usize(bitstream.take(15).collect::<BitVec>());
-                        Packet::Operator{version, packet_type, subpackets: read_n_bits(&mut bitstream, bits_to_read)}
+                        Packet::Operator{version, packet_type, length_type: LengthType::Bits, subpackets: read_n_bits(&mut bitstream, bits_to_read)}
                     },
                     true => {
                         let packets_to_read = bits_to_usize(bitstream.take(11).collect::<BitVec>());
-                        Packet::Operator{version, packet_type, subpackets: read_n_packets(&mut bitstream, packets_to_read)}
+                        Packet::Operator{version, packet_type, length_type: LengthType::Count, subpackets: read_n_packets(&mut bitstream, packets_to_read)}
                     }
                 }
             }
@@ -43,6 +51,91 @@ impl Packet {
     }
 }
 
+// Building and encoding packets, used to generate transmissions for the decoder's tests
+#[cfg_attr(not(test), allow(dead_code))]
+impl Packet {
+    fn literal(version: usize, value: usize) -> Self {
+        Packet::Literal{version, packet_type: 4, value}
+    }
+
+    fn operator(version: usize, packet_type: usize, length_type: LengthType, subpackets: Vec<Packet>) -> Self {
+        Packet::Operator{version, packet_type, length_type, subpackets}
+    }
+
+    fn to_bits(&self, bits: &mut BitVec) -> Result<(), String> {
+        match self {
+            Packet::Literal{version, packet_type, value} => {
+                push_usize(bits, *version, 3)?;
+                push_usize(bits, *packet_type, 3)?;
+                let groups = (usize::BITS - value.leading_zeros()).max(1).div_ceil(4) as usize;
+                for group in (0..groups).rev() {
+                    bits.push(group > 0);
+                    push_usize(bits, (value >> (4 * group)) & 0xF, 4)?;
+                }
+            },
+            Packet::Operator{version, packet_type, length_type, subpackets} => {
+                push_usize(bits, *version, 3)?;
+                push_usize(bits, *packet_type, 3)?;
+                match length_type {
+                    LengthType::Bits => {
+                        let mut contents = BitVec::new();
+                        for subpacket in subpackets {
+                            subpacket.to_bits(&mut contents)?;
+                        }
+                        bits.push(false);
+                        push_usize(bits, contents.len(), 15)?;
+                        bits.append(&mut contents);
+                    },
+                    LengthType::Count => {
+                        bits.push(true);
+                        push_usize(bits, subpackets.len(), 11)?;
+                        for subpacket in subpackets {
+                            subpacket.to_bits(bits)?;
+                        }
+                    },
+                }
+            },
+        }
+        Ok(())
+    }
+
+    // Encodes the packet as a transmission, padded with zeros to a whole number of bytes
+    fn to_hex(&self) -> Result<String, String> {
+        let mut bits = BitVec::new();
+        self.to_bits(&mut bits)?;
+        Ok(hex::encode_upper(bits.to_bytes()))
+    }
+}
+
+fn operator_name(packet_type: usize) -> &'static str {
+    match packet_type {
+        0 => "sum",
+        1 => "product",
+        2 => "min",
+        3 => "max",
+        5 => "gt",
+        6 => "lt",
+        7 => "eq",
+        _ => "unknown",
+    }
+}
+
+// Prints the expression a packet describes, such as `(sum (product 6 9) 3)`
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Packet::Literal{value, ..} => write!(f, "{}", value),
+            Packet::Operator{packet_type, subpackets, ..} => {
+                write!(f, "({}", operator_name(*packet_type))?;
+                for subpacket in subpackets {
+                    write!(f, " {}", subpacket)?;
+                }
+                write!(f, ")")
+            },
+        }
+    }
+}
+
 impl FromStr for Packet {
     type Err = std::string::ParseError;
 
@@ -84,6 +177,17 @@ fn read_literal_value(bitstream: &mut bit_vec::Iter) -> usize {
     bits_to_usize(bits)
 }
 
+#[cfg_attr(not(test), allow(dead_code))]
+fn push_usize(bits: &mut BitVec, value: usize, width: usize) -> Result<(), String> {
+    if value >> width != 0 {
+        return Err(format!("{} doesn't fit in {} bits", value, width));
+    }
+    for i in (0..width).rev() {
+        bits.push(value >> i & 1 == 1);
+    }
+    Ok(())
+}
+
 fn bits_to_usize(mut bits: BitVec) -> usize {
     let bytes = (bits.len()+7)/8;
     let mut new_bits = BitVec::from_elem((bytes*8)-bits.len(), false);
@@ -100,21 +204,21 @@ fn parse_input(input: &str) -> Packet {
 
 fn sum_versions(packet: &Packet) -> usize {
     match packet {
-        Packet::Literal{version, packet_type: _,  value: _} => *version,
-        Packet::Operator{version, packet_type: _,  subpackets} => *version + subpackets.iter().map(sum_versions).sum::<usize>(),
+        Packet::Literal{version, ..} => *version,
+        Packet::Operator{version, subpackets, ..} => *version + subpackets.iter().map(sum_versions).sum::<usize>(),
     }
 }
 
 fn evaluate(packet: &Packet) -> usize {
     match packet {
         Packet::Literal{version: _, packet_type: _, value} => *value,
-        Packet::Operator{version: _, packet_type: 0, subpackets} => subpackets.iter().map(evaluate).sum::<usize>(),
-        Packet::Operator{version: _, packet_type: 1, subpackets} => subpackets.iter().map(evaluate).product::<usize>(),
-        Packet::Operator{version: _, packet_type: 2, subpackets} => subpackets.iter().map(evaluate).min().unwrap(),
-        Packet::Operator{version: _, packet_type: 3, subpackets} => subpackets.iter().map(evaluate).max().unwrap(),
-        Packet::Operator{version: _, packet_type: 5, subpackets} => (evaluate(&subpackets[0]) > evaluate(&subpackets[1])) as usize,
-        Packet::Operator{version: _, packet_type: 6, subpackets} => (evaluate(&subpackets[0]) < evaluate(&subpackets[1])) as usize,
-        Packet::Operator{version: _, packet_type: 7, subpackets} => (evaluate(&subpackets[0]) == evaluate(&subpackets[1])) as usize,
+        Packet::Operator{packet_type: 0, subpackets, ..} => subpackets.iter().map(evaluate).sum::<usize>(),
+        Packet::Operator{packet_type: 1, subpackets, ..} => subpackets.iter().map(evaluate).product::<usize>(),
+        Packet::Operator{packet_type: 2, subpackets, ..} => subpackets.iter().map(evaluate).min().unwrap(),
+        Packet::Operator{packet_type: 3, subpackets, ..} => subpackets.iter().map(evaluate).max().unwrap(),
+        Packet::Operator{packet_type: 5, subpackets, ..} => (evaluate(&subpackets[0]) > evaluate(&subpackets[1])) as usize,
+        Packet::Operator{packet_type: 6, subpackets, ..} => (evaluate(&subpackets[0]) < evaluate(&subpackets[1])) as usize,
+        Packet::Operator{packet_type: 7, subpackets, ..} => (evaluate(&subpackets[0]) == evaluate(&subpackets[1])) as usize,
         _ => panic!("Unknown packet type: {:?}", packet),
     }
 }
@@ -154,6 +258,7 @@ fn main() {
 mod tests {
     use super::parse_input;
     use super::Packet;
+    use super::LengthType;
     use super::part_1;
     use super::part_2;
     #[test]
@@ -169,7 +274,7 @@ mod tests {
     fn example2() {
         let input = "38006F45291200";
         let packet = parse_input(input);
-        assert_eq!(packet, Packet::Operator{version: 1, packet_type: 6, subpackets: vec![Packet::Literal{version: 6, packet_type: 4, value: 10}, Packet::Literal{version: 2, packet_type: 4, value: 20}]});
+        assert_eq!(packet, Packet::Operator{version: 1, packet_type: 6, length_type: LengthType::Bits, subpackets: vec![Packet::Literal{version: 6, packet_type: 4, value: 10}, Packet::Literal{version: 2, packet_type: 4, value: 20}]});
         assert_eq!(part_1(&packet), 9);
         assert_eq!(part_2(&packet), 1);
     }
@@ -178,8 +283,30 @@ mod tests {
     fn example3() {
         let input = "EE00D40C823060";
         let packet = parse_input(input);
-        assert_eq!(packet, Packet::Operator{version: 7, packet_type: 3, subpackets: vec![Packet::Literal{version: 2, packet_type: 4, value: 1}, Packet::Literal{version: 4, packet_type: 4, value: 2}, Packet::Literal{version: 1, packet_type: 4, value: 3}]});
+        assert_eq!(packet, Packet::Operator{version: 7, packet_type: 3, length_type: LengthType::Count, subpackets: vec![Packet::Literal{version: 2, packet_type: 4, value: 1}, Packet::Literal{version: 4, packet_type: 4, value: 2}, Packet::Literal{version: 1, packet_type: 4, value: 3}]});
         assert_eq!(part_1(&packet), 14);
         assert_eq!(part_2(&packet), 3);
     }
+
+    #[test]
+    fn encoding() {
+        for input in ["D2FE28", "38006F45291200", "EE00D40C823060", "9C0141080250320F1802104A08"] {
+            assert_eq!(parse_input(input).to_hex(), Ok(input.to_string()));
+        }
+
+        let packet = Packet::operator(3, 0, LengthType::Bits, vec![
+            Packet::operator(5, 1, LengthType::Count, vec![Packet::literal(0, 6), Packet::literal(7, 9)]),
+            Packet::literal(2, 3),
+        ]);
+        assert_eq!(packet.to_string(), "(sum (product 6 9) 3)");
+        let decoded = parse_input(&packet.to_hex().unwrap());
+        assert_eq!(decoded, packet);
+        assert_eq!(part_1(&decoded), 17);
+        assert_eq!(part_2(&decoded), 57);
+
+        let big = Packet::literal(1, 0x123456789);
+        assert_eq!(parse_input(&big.to_hex().unwrap()), big);
+        assert_eq!(parse_input("9C0141080250320F1802104A08").to_string(), "(eq (sum 1 3) (product 2 2))");
+        assert!(Packet::literal(8, 0).to_hex().is_err());
+    }
 }
@@ -1,130 +1,141 @@
 use std::fs;
 use std::env;
+use std::fmt;
+use std::ops::Add;
+use std::str::FromStr;
 use std::time::SystemTime;
 use itertools::Itertools;
+use aoc::parse::{alt, map, pair, parse_all, parse_lines, preceded, tag, terminated, unsigned, Input, ParseError, ParseResult};
 
 #[derive(Debug, PartialEq, Clone, Eq, Hash)]
-enum Direction {
-    Left,
-    Right,
+enum SnailfishNumber {
+    Regular(u64),
+    Pair(Box<SnailfishNumber>, Box<SnailfishNumber>),
 }
 
-#[derive(Debug, PartialEq, Clone, Eq, Hash)]
-struct Item {
-    value: u32,
-    path: Vec<Direction>
-}
+use SnailfishNumber::{Pair, Regular};
 
-fn parse_line(line: &str) -> Vec<Item> {
-    let mut result = Vec::new();
-    let mut path = Vec::new();
-    line.chars().for_each(|c: char| match c {
-        '[' => {path.push(Direction::Left);},
-        ']' => {path.pop();},
-        ',' => {path.pop(); path.push(Direction::Right);},
-        _ => result.push(Item{
-            value: c.to_digit(10).unwrap(),
-            path: path.clone()
-        }),
-    });
-    result
+fn snailfish(input: Input) -> ParseResult<SnailfishNumber> {
+    let regular = map(unsigned, Regular);
+    let pair = map(
+        preceded(tag("["), terminated(pair(terminated(snailfish, tag(",")), snailfish), tag("]"))),
+        |(left, right)| Pair(Box::new(left), Box::new(right)),
+    );
+    alt(regular, pair)(input)
 }
 
-fn parse_input(input: &str) -> Vec<Vec<Item>> {
-    input.trim()
-        .lines()
-        .map(parse_line)
-        .collect()
+impl FromStr for SnailfishNumber {
+    type Err = ParseError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        parse_all(snailfish, line)
+    }
 }
 
-fn path_value(path: &Vec<Direction>) -> u32 {
-    path.iter()
-        .map(|dir| match dir {
-            Direction::Left => 3,
-            Direction::Right => 2,
-        })
-        .product()
+impl fmt::Display for SnailfishNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Regular(n) => write!(f, "{}", n),
+            Pair(left, right) => write!(f, "[{},{}]", left, right),
+        }
+    }
 }
 
-fn magnitude(items: &Vec<Item>) -> u32 {
-    items.iter()
-        .map(|item| path_value(&item.path) * item.value)
-        .sum()
+impl Add for SnailfishNumber {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let mut result = Pair(Box::new(self), Box::new(other));
+        result.reduce();
+        result
+    }
 }
 
-fn explode(items: &Vec<Item>) -> Option<Vec<Item>> {
-    let mut result = items.clone();
-    let pos = result.iter().position(|item| item.path.len() > 4)?;
-    if pos > 0 {
-        result[pos - 1].value += result[pos].value;
+impl SnailfishNumber {
+    fn magnitude(&self) -> u64 {
+        match self {
+            Regular(n) => *n,
+            Pair(left, right) => 3 * left.magnitude() + 2 * right.magnitude(),
+        }
     }
-    if pos + 2 < result.len() {
-        result[pos + 2].value += result[pos + 1].value;
+
+    fn add_leftmost(&mut self, value: u64) {
+        match self {
+            Regular(n) => *n += value,
+            Pair(left, _) => left.add_leftmost(value),
+        }
     }
-    result.splice(
-        pos..=pos+1,
-        vec![Item{
-            value: 0,
-            path: result[pos].path[..result[pos].path.len() - 1].into(),
-        }]
-    );
-    Some(result)
-}
 
-fn split(items: &Vec<Item>) -> Option<Vec<Item>> {
-    let mut result = items.clone();
-    let pos = result.iter().position(|item| item.value >= 10)?;
-    result.splice(
-        pos..=pos,
-        vec![
-            Item{
-                value: result[pos].value / 2,
-                path: [result[pos].path.clone(), vec![Direction::Left]].concat(),
-            },
-            Item{
-                value: (result[pos].value+1) / 2,
-                path: [result[pos].path.clone(), vec![Direction::Right]].concat(),
-            }
-        ]
-    );
-    Some(result)
-}
+    fn add_rightmost(&mut self, value: u64) {
+        match self {
+            Regular(n) => *n += value,
+            Pair(_, right) => right.add_rightmost(value),
+        }
+    }
 
-fn reduce(items: &Vec<Item>) -> Vec<Item> {
-    let mut current = items.clone();
-    loop {
-        let exploded = explode(&current);
-        if exploded.is_some() {
-            current = exploded.unwrap();
-            continue
+    // Explodes the leftmost pair nested inside four others, returning the values still to be
+    // added to the numbers either side of it
+    fn explode_at(&mut self, depth: usize) -> Option<(Option<u64>, Option<u64>)> {
+        let (left, right) = match self {
+            Regular(_) => return None,
+            Pair(left, right) => (left, right),
+        };
+        if depth >= 4 {
+            if let (Regular(a), Regular(b)) = (&**left, &**right) {
+                let carry = (Some(*a), Some(*b));
+                *self = Regular(0);
+                return Some(carry);
+            }
+        }
+        if let Some((carry_left, carry_right)) = left.explode_at(depth + 1) {
+            if let Some(value) = carry_right {
+                right.add_leftmost(value);
+            }
+            return Some((carry_left, None));
         }
-        let split = split(&current);
-        if split.is_some() {
-            current = split.unwrap();
-            continue
+        if let Some((carry_left, carry_right)) = right.explode_at(depth + 1) {
+            if let Some(value) = carry_left {
+                left.add_rightmost(value);
+            }
+            return Some((None, carry_right));
         }
-        break
+        None
     }
-    current
-}
 
-fn add(left: &Vec<Item>, right: &Vec<Item>) -> Vec<Item> {
-    let result = [
-        left.iter().map(|item| {let mut new = item.clone(); new.path.insert(0, Direction::Left); new}).collect::<Vec<Item>>(),
-        right.iter().map(|item| {let mut new = item.clone(); new.path.insert(0, Direction::Right); new}).collect::<Vec<Item>>(),
-    ].concat();
+    // A single explode step, returning whether anything exploded
+    fn explode(&mut self) -> bool {
+        self.explode_at(0).is_some()
+    }
+
+    // A single split step, returning whether anything split
+    fn split(&mut self) -> bool {
+        match self {
+            Regular(n) if *n >= 10 => {
+                *self = Pair(Box::new(Regular(*n / 2)), Box::new(Regular((*n + 1) / 2)));
+                true
+            },
+            Regular(_) => false,
+            Pair(left, right) => left.split() || right.split(),
+        }
+    }
+
+    fn reduce(&mut self) {
+        while self.explode() || self.split() {}
+    }
+}
 
-    reduce(&result)
+fn parse_input(input: &str) -> Vec<SnailfishNumber> {
+    parse_lines(snailfish, input).unwrap_or_else(|e| panic!("Bad input: {}", e))
 }
 
-fn part_1(items: &Vec<Vec<Item>>) -> u32 {
-    magnitude(&items.iter().cloned().reduce(|acc, item| add(&acc, &item)).unwrap())
+fn part_1(numbers: &Vec<SnailfishNumber>) -> u64 {
+    numbers.iter().cloned().reduce(|acc, number| acc + number).unwrap().magnitude()
 }
 
-fn part_2(items: &Vec<Vec<Item>>) -> u32 {
-    items.iter()
+fn part_2(numbers: &Vec<SnailfishNumber>) -> u64 {
+    numbers.iter()
         .permutations(2)
-        .map(|nums| magnitude(&add(&nums[0], &nums[1])))
+        .map(|nums| (nums[0].clone() + nums[1].clone()).magnitude())
         .max()
         .unwrap()
 }
@@ -155,18 +166,16 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::parse_input;
-    use super::magnitude;
-    use super::explode;
-    use super::split;
-    use super::add;
-    use super::Item;
-    use super::Direction::*;
+    use super::SnailfishNumber;
     use super::part_1;
     use super::part_2;
 
+    fn number(line: &str) -> SnailfishNumber {
+        line.parse().unwrap()
+    }
+
     #[test]
     fn example1() {
-        use super::Item;
         let input =
 "[1,1]
 [2,2]
@@ -174,18 +183,15 @@ mod tests {
 [4,4]";
         let pairs = parse_input(input);
         assert_eq!(
-            pairs,
-            vec![
-                vec![Item{value: 1, path: vec![Left]}, Item{value: 1, path: vec![Right]}],
-                vec![Item{value: 2, path: vec![Left]}, Item{value: 2, path: vec![Right]}],
-                vec![Item{value: 3, path: vec![Left]}, Item{value: 3, path: vec![Right]}],
-                vec![Item{value: 4, path: vec![Left]}, Item{value: 4, path: vec![Right]}],
-            ]
+            pairs.iter().map(|p| p.to_string()).collect::<Vec<String>>(),
+            input.lines().collect::<Vec<_>>()
         );
         assert_eq!(
-            pairs.iter().map(magnitude).collect::<Vec<u32>>(),
+            pairs.iter().map(|p| p.magnitude()).collect::<Vec<u64>>(),
             vec![5, 10, 15, 20]
         );
+        let error = "[1,[2,3]".parse::<SnailfishNumber>().unwrap_err();
+        assert_eq!((error.line, error.column), (1, 9));
     }
 
     #[test]
@@ -200,7 +206,7 @@ mod tests {
 
         let pairs = parse_input(input);
         assert_eq!(
-            pairs.iter().map(magnitude).collect::<Vec<u32>>(),
+            pairs.iter().map(|p| p.magnitude()).collect::<Vec<u64>>(),
             vec![143, 1384, 445, 791, 1137, 3488]
         );
     }
@@ -225,51 +231,44 @@ mod tests {
         inputs.iter()
             .zip(outputs.iter())
             .for_each(|(input, output)| {
-                assert_eq!(
-                    &explode(input).unwrap(),
-                    output
-                );
+                let mut exploded = input.clone();
+                assert!(exploded.explode());
+                assert_eq!(&exploded, output);
             });
     }
 
     #[test]
     fn example4() {
-        let inputs = vec![
-            // [15,[0,13]]
-            vec![Item{value: 15, path: vec![Left]}, Item{value: 0, path: vec![Right, Left]}, Item{value: 13, path: vec![Right, Right]}],
-            // [[7,8],[0,13]]
-            vec![Item{value: 7, path: vec![Left, Left]}, Item{value: 8, path: vec![Left, Right]}, Item{value: 0, path: vec![Right, Left]}, Item{value: 13, path: vec![Right, Right]}],
-        ];
-        let outputs = vec![
-            //[[7,8],[0,13]]
-            vec![Item{value: 7, path: vec![Left, Left]}, Item{value: 8, path: vec![Left, Right]}, Item{value: 0, path: vec![Right, Left]}, Item{value: 13, path: vec![Right, Right]}],
-            //[[7,8],[0,[6,7]]]
-            vec![Item{value: 7, path: vec![Left, Left]}, Item{value: 8, path: vec![Left, Right]}, Item{value: 0, path: vec![Right, Left]}, Item{value: 6, path: vec![Right, Right, Left]}, Item{value: 7, path: vec![Right, Right, Right]}],
-        ];
-
-        inputs.iter()
-            .zip(outputs.iter())
-            .for_each(|(input, output)| {
-                assert_eq!(
-                    &split(input).unwrap(),
-                    output
-                );
-            });
+        let mut current = number("[15,[0,13]]");
+        assert!(current.split());
+        assert_eq!(current.to_string(), "[[7,8],[0,13]]");
+        assert!(current.split());
+        assert_eq!(current.to_string(), "[[7,8],[0,[6,7]]]");
+        assert!(!current.split());
     }
 
     #[test]
     fn example5() {
-        let input_text =
-"[[[[4,3],4],4],[7,[[8,4],9]]]
-[1,1]";
-        let output_text =
-"[[[[0,7],4],[[7,8],[6,0]]],[8,1]]";
+        // Each reduction step from the worked example, in order
+        let mut current = number("[[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]");
+        let steps = vec![
+            (true, "[[[[0,7],4],[7,[[8,4],9]]],[1,1]]"),
+            (true, "[[[[0,7],4],[15,[0,13]]],[1,1]]"),
+            (false, "[[[[0,7],4],[[7,8],[0,13]]],[1,1]]"),
+            (false, "[[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]"),
+            (true, "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]"),
+        ];
+        for (explodes, expected) in steps {
+            assert_eq!(current.explode(), explodes);
+            if !explodes {
+                assert!(current.split());
+            }
+            assert_eq!(current.to_string(), expected);
+        }
 
-        let inputs = parse_input(input_text);
-        let outputs = parse_input(output_text);
         assert_eq!(
-            add(&inputs[0], &inputs[1]),
-            outputs[0]
+            number("[[[[4,3],4],4],[7,[[8,4],9]]]") + number("[1,1]"),
+            current
         );
     }
 
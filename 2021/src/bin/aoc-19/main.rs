@@ -4,49 +4,18 @@ use std::time::SystemTime;
 use std::str::FromStr;
 use std::collections::HashSet;
 use itertools::Itertools;
-use std::ops::Sub;
+use aoc::point::Point3;
+use aoc::rotation::{build_map, Cloud};
 #[macro_use] extern crate scan_fmt;
 
-#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash)]
-struct Pos {
-    x: i32,
-    y: i32,
-    z: i32,
-}
-
-impl Sub for Pos {
-    type Output = Self;
-
-    fn sub(self, other: Self) -> Self {
-        Self {
-            x: self.x - other.x,
-            y: self.y - other.y,
-            z: self.z - other.z,
-        }
-    }
-}
-
-impl FromStr for Pos {
-    type Err = std::string::ParseError;
-
-    fn from_str(line: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = line.split(",").collect();
-        Ok(Pos{
-            x: i32::from_str_radix(parts[0], 10).unwrap(),
-            y: i32::from_str_radix(parts[1], 10).unwrap(),
-            z: i32::from_str_radix(parts[2], 10).unwrap(),
-        })
-    }
-}
-
 #[derive(Debug, PartialEq, Clone, Eq)]
 struct Scanner {
-    beacons: HashSet<Pos>,
+    beacons: Vec<Point3>,
     id: i32,
 }
 
 impl FromStr for Scanner {
-    type Err = std::string::ParseError;
+    type Err = String;
 
     fn from_str(section: &str) -> Result<Self, Self::Err> {
         let parts: Vec<&str> = section.lines().collect();
@@ -55,11 +24,11 @@ impl FromStr for Scanner {
             parts[0],
             "--- scanner {} ---",
             i32
-        ).unwrap();
+        ).map_err(|e| format!("Bad header {:?}: {}", parts[0], e))?;
 
         let beacons = parts[1..].iter()
-            .map(|line| Pos::from_str(line).unwrap())
-            .collect();
+            .map(|line| Point3::from_str(line))
+            .collect::<Result<_, _>>()?;
 
         Ok(Scanner{id, beacons})
     }
@@ -72,75 +41,16 @@ fn parse_input(input: &str) -> Vec<Scanner> {
         .collect()
 }
 
-fn get_rotations(scanner: &Scanner) -> Vec<Scanner> {
-    let permutations: Vec<Box<dyn Fn(&Pos) -> Pos>> = vec![
-        Box::new(|b: &Pos| Pos{x: b.x, y: b.y, z: b.z}),
-        Box::new(|b: &Pos| Pos{x: b.x, y: b.z, z: b.y}),
-        Box::new(|b: &Pos| Pos{x: b.y, y: b.x, z: b.z}),
-        Box::new(|b: &Pos| Pos{x: b.y, y: b.z, z: b.x}),
-        Box::new(|b: &Pos| Pos{x: b.z, y: b.x, z: b.y}),
-        Box::new(|b: &Pos| Pos{x: b.z, y: b.y, z: b.x}),
-    ];
-    let rotations: Vec<Box<dyn Fn(&Pos) -> Pos>> = vec![
-        Box::new(|b: &Pos| Pos{x:  b.x, y:  b.y, z:  b.z}),
-        Box::new(|b: &Pos| Pos{x:  b.x, y:  b.y, z: -b.z}),
-        Box::new(|b: &Pos| Pos{x:  b.x, y: -b.y, z:  b.z}),
-        Box::new(|b: &Pos| Pos{x:  b.x, y: -b.y, z: -b.z}),
-        Box::new(|b: &Pos| Pos{x: -b.x, y:  b.y, z:  b.z}),
-        Box::new(|b: &Pos| Pos{x: -b.x, y:  b.y, z: -b.z}),
-        Box::new(|b: &Pos| Pos{x: -b.x, y: -b.y, z:  b.z}),
-        Box::new(|b: &Pos| Pos{x: -b.x, y: -b.y, z: -b.z}),
-    ];
-
-    permutations.iter()
-        .cartesian_product(&rotations)
-        .map(|(perm, rot)| {
-            Scanner{
-                id: scanner.id,
-                beacons: scanner.beacons.iter()
-                    .map(|b| perm(&b))
-                    .map(|b| rot(&b))
-                    .collect(),
-            }
-        })
-        .collect()
-}
-
-fn correlate_map(scanners: &Vec<Scanner>) -> (HashSet<Pos>, Vec<Pos>) {
-    let mut remaining = scanners.clone();
-    let mut map: HashSet<_> = remaining.pop().unwrap().beacons;
-    let mut scanner_positions = vec![Pos{x: 0, y: 0, z: 0}];
-
-    while remaining.len() > 0 {
-        let base_candidate = remaining.pop().unwrap();
-        let mut found = false;
-
-        'outer: for candidate in get_rotations(&base_candidate) {
-            for map_beacon in map.clone() {
-                for scanner_beacon in &candidate.beacons {
-                    let difference = *scanner_beacon - map_beacon;
-
-                    let test_beacons: HashSet<_> = candidate.beacons.iter()
-                        .map(|beacon| *beacon - difference)
-                        .collect();
-                    let common: HashSet<_> = map.intersection(&test_beacons).collect();
-                    if common.len() >= 12 {
-                        println!("found match for scanner {} at {:?} ({} matches)!", candidate.id, difference, common.len());
-                        map.extend(test_beacons);
-                        found = true;
-                        scanner_positions.push(difference);
-                        break 'outer;
-                    }
-                }
-            }
-        }
-
-        if !found {
-            remaining.insert(0, base_candidate);
-        }
-    }
+fn correlate_map(scanners: &Vec<Scanner>) -> (HashSet<Point3>, Vec<Point3>) {
+    let clouds = scanners.iter()
+        .map(|scanner| Cloud::new(scanner.beacons.clone()))
+        .collect_vec();
+    let map = build_map(&clouds, 12).unwrap();
+    let scanner_positions = map.poses.iter()
+        .map(|pose| pose.translation)
+        .collect();
 
-    (map, scanner_positions)
+    (map.points, scanner_positions)
 }
 
 fn part_1(scanners: &Vec<Scanner>) -> usize {
@@ -148,11 +58,11 @@ fn part_1(scanners: &Vec<Scanner>) -> usize {
     map.len()
 }
 
-fn part_2(scanners: &Vec<Scanner>) -> i32 {
+fn part_2(scanners: &Vec<Scanner>) -> i64 {
     let (_, scanner_positions) = correlate_map(&scanners);
     scanner_positions.iter()
         .permutations(2)
-        .map(|a| (a[0].x - a[1].x).abs() + (a[0].y - a[1].y).abs() + (a[0].z - a[1].z).abs())
+        .map(|a| a[0].manhattan(a[1]))
         .max()
         .unwrap()
 }
@@ -8,15 +8,10 @@ use itertools::Itertools;
 use std::convert::Infallible;
 use std::ops::{Add};
 use std::cmp::{max, min};
+use aoc::boxes::{BoxSet, Cuboid};
+use aoc::point::Point3;
 #[macro_use] extern crate scan_fmt;
 
-#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash)]
-struct Pos {
-    x: i64,
-    y: i64,
-    z: i64,
-}
-
 #[derive(Debug, PartialEq, Clone, Copy, Eq, Hash)]
 enum State {
     On,
@@ -41,61 +36,7 @@ impl FromStr for State {
 #[derive(Debug, PartialEq, Clone, Copy, Eq, Hash)]
 struct Cube {
     state: State,
-    lowest: Pos,
-    highest: Pos,
-}
-
-impl Cube {
-    fn volume(self) -> i64 {
-        max(0, self.highest.x - self.lowest.x)
-        * max(0, self.highest.y - self.lowest.y)
-        * max(0, self.highest.z - self.lowest.z)
-    }
-
-    fn contains(self, other: &Self) -> bool {
-        (self.lowest.x <= other.lowest.x && self.highest.x >= other.highest.x)
-        && (self.lowest.y <= other.lowest.y && self.highest.y >= other.highest.y)
-        && (self.lowest.z <= other.lowest.z && self.highest.z >= other.highest.z)
-    }
-
-    fn intersects(self, other: &Self) -> bool {
-        !(self.lowest.x >= other.highest.x || self.highest.x <= other.lowest.x)
-        && !(self.lowest.y >= other.highest.y || self.highest.y <= other.lowest.y)
-        && !(self.lowest.z >= other.highest.z || self.highest.z <= other.lowest.z)
-    }
-
-    fn breakup(self, other: &Self) -> Vec<Cube> {
-        let x_segments = vec![
-            (self.lowest.x, min(other.lowest.x, self.highest.x)),
-            (max(other.lowest.x, self.lowest.x), min(other.highest.x, self.highest.x)),
-            (max(other.highest.x, self.lowest.x), self.highest.x),
-        ];
-
-        let y_segments = vec![
-            (self.lowest.y, min(other.lowest.y, self.highest.y)),
-            (max(other.lowest.y, self.lowest.y), min(other.highest.y, self.highest.y)),
-            (max(other.highest.y, self.lowest.y), self.highest.y),
-        ];
-
-        let z_segments = vec![
-            (self.lowest.z, min(other.lowest.z, self.highest.z)),
-            (max(other.lowest.z, self.lowest.z), min(other.highest.z, self.highest.z)),
-            (max(other.highest.z, self.lowest.z), self.highest.z),
-        ];
-
-        x_segments.iter()
-            .cartesian_product(y_segments.iter())
-            .cartesian_product(z_segments.iter())
-            .map(|(((x_low, x_high), (y_low, y_high)), (z_low, z_high))| {
-                Cube{
-                    state: self.state,
-                    lowest: Pos{x: *x_low, y: *y_low, z: *z_low},
-                    highest: Pos{x: *x_high, y: *y_high, z: *z_high},
-                }
-            })
-            .filter(|c| c.volume() > 0)
-            .collect()
-    }
+    bounds: Cuboid,
 }
 
 impl FromStr for Cube {
@@ -106,16 +47,10 @@ impl FromStr for Cube {
 
         Ok(Cube{
             state: State::from_str(&state).unwrap(),
-            lowest: Pos{
-                x: x1,
-                y: y1,
-                z: z1,
-            },
-            highest: Pos{
-                x: x2 + 1,
-                y: y2 + 1,
-                z: z2 + 1,
-            },
+            bounds: Cuboid::from_inclusive(
+                Point3::new(x1, y1, z1),
+                Point3::new(x2, y2, z2),
+            ),
         })
     }
 }
@@ -128,60 +63,29 @@ fn parse_input(input: &str) -> Vec<Cube> {
         .collect()
 }
 
-fn add_to_existing(new_cube: &Cube, existing_cubes: &Vec<Cube>) -> Vec<Cube> {
-    let mut cubes_to_check = existing_cubes.clone();
-    let mut next = vec![];
-    while !cubes_to_check.is_empty() {
-        let existing_cube = cubes_to_check.pop().unwrap();
-        if new_cube.contains(&existing_cube) {
-            continue;
-        }
-        else if !new_cube.intersects(&existing_cube) {
-            next.push(existing_cube.clone());
-        }
-        else {
-            existing_cube.breakup(new_cube)
-                .iter()
-                .for_each(|c| cubes_to_check.push(*c));
+fn apply_cubes<'a>(cubes: impl Iterator<Item = &'a Cube>) -> BoxSet<3> {
+    let mut reactor = BoxSet::new();
+    cubes.for_each(|cube| {
+        match cube.state {
+            State::On => reactor.add(&cube.bounds),
+            State::Off => reactor.subtract(&cube.bounds),
         }
-    }
-    next.push(new_cube.clone());
-
-    next
-}
-
-fn apply_cubes(cubes: &Vec<Cube>) -> Vec<Cube> {
-    let mut existing_cubes = vec![];
-    cubes.iter()
-        .for_each(|new_cube| {
-            existing_cubes = add_to_existing(&new_cube, &existing_cubes);
-        });
-    existing_cubes
+    });
+    reactor
 }
 
 fn part_1(cubes: &Vec<Cube>) -> i64 {
-    let filter_zone = Cube{
-        state: State::On,
-        lowest: Pos{x: -50, y: -50, z: -50},
-        highest: Pos{x: 51, y: 51, z: 51}
-    };
-    let small_cubes: Vec<Cube> = cubes.iter()
-        .filter(|c| filter_zone.contains(c))
-        .cloned()
-        .collect();
-    apply_cubes(&small_cubes)
-    .iter()
-    .filter(|c| c.state == State::On)
-    .map(|c| c.volume())
-    .sum()
+    let filter_zone = Cuboid::from_inclusive(
+        Point3::new(-50, -50, -50),
+        Point3::new(50, 50, 50),
+    );
+    apply_cubes(cubes.iter().filter(|c| filter_zone.contains_box(&c.bounds)))
+        .volume()
 }
 
 fn part_2(cubes: &Vec<Cube>) -> i64 {
-    apply_cubes(cubes)
-    .iter()
-    .filter(|c| c.state == State::On)
-    .map(|c| c.volume())
-    .sum()
+    apply_cubes(cubes.iter())
+        .volume()
 }
 
 fn main() {
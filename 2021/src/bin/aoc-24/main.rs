@@ -6,6 +6,10 @@ use std::fs;
 use std::str::FromStr;
 use std::time::SystemTime;
 extern crate scan_fmt;
+use aoc::parse::{
+    alt, map, one_of, optional, parse_all, parse_lines, signed, spaces, word, ws, Input,
+    ParseError, ParseResult,
+};
 
 #[derive(Debug, PartialEq, Clone, Copy, Eq, Hash)]
 enum Instruction {
@@ -17,18 +21,24 @@ enum Instruction {
     Equal,
 }
 
+fn instruction(input: Input) -> ParseResult<Instruction> {
+    let (name, rest) = word(input)?;
+    let instruction = match name {
+        "inp" => Instruction::Input,
+        "add" => Instruction::Add,
+        "mul" => Instruction::Multiply,
+        "div" => Instruction::Divide,
+        "mod" => Instruction::Modulo,
+        "eql" => Instruction::Equal,
+        _ => return Err(input.error(format!("Unexpected instruction: {:?}", name))),
+    };
+    Ok((instruction, rest))
+}
+
 impl FromStr for Instruction {
-    type Err = std::string::ParseError;
+    type Err = ParseError;
     fn from_str(word: &str) -> Result<Self, Self::Err> {
-        match word {
-            "inp" => Ok(Instruction::Input),
-            "add" => Ok(Instruction::Add),
-            "mul" => Ok(Instruction::Multiply),
-            "div" => Ok(Instruction::Divide),
-            "mod" => Ok(Instruction::Modulo),
-            "eql" => Ok(Instruction::Equal),
-            _ => panic!("Unexpected instruction: {:?}", word),
-        }
+        parse_all(instruction, word)
     }
 }
 
@@ -36,7 +46,13 @@ impl FromStr for Instruction {
 struct Command {
     instruction: Instruction,
     output: String,
-    other: Option<String>,
+    other: Option<Operand>,
+}
+
+#[derive(Debug, PartialEq, Clone, Eq, Hash)]
+enum Operand {
+    Register(String),
+    Value(i64),
 }
 
 impl Command {
@@ -51,18 +67,18 @@ impl Command {
         let min_other = self
             .other
             .as_ref()
-            .map(|o| match o.as_str() {
-                "w" | "x" | "y" | "z" => minimums[o],
-                _ => i64::from_str(o.as_str()).unwrap(),
+            .map(|o| match o {
+                Operand::Register(r) => minimums[r],
+                Operand::Value(v) => *v,
             })
             .unwrap_or(0);
         let max_out = maximums[&self.output].clone();
         let max_other = self
             .other
             .as_ref()
-            .map(|o| match o.as_str() {
-                "w" | "x" | "y" | "z" => maximums[o],
-                _ => i64::from_str(o.as_str()).unwrap(),
+            .map(|o| match o {
+                Operand::Register(r) => maximums[r],
+                Operand::Value(v) => *v,
             })
             .unwrap_or(0);
 
@@ -150,17 +166,45 @@ impl Command {
     }
 }
 
+// Exactly one of w, x, y or z, not the start of a longer name
+fn register(input: Input) -> ParseResult<String> {
+    let (name, rest) = one_of("wxyz")(input)?;
+    match rest.rest().chars().next() {
+        Some(c) if c.is_alphanumeric() => Err(input.expected("one of \"wxyz\"")),
+        _ => Ok((name.to_string(), rest)),
+    }
+}
+
+fn operand(input: Input) -> ParseResult<Operand> {
+    alt(
+        map(register, Operand::Register),
+        map(signed, Operand::Value),
+    )(input)
+}
+
+fn command(input: Input) -> ParseResult<Command> {
+    let (instruction, rest) = instruction(input)?;
+    let (output, rest) = ws(register)(rest)?;
+    let (other, after) = optional(ws(operand))(rest)?;
+    match (instruction, &other) {
+        (Instruction::Input, Some(_)) => Err(spaces(rest)?.1.expected("the end of the line")),
+        (Instruction::Input, None) | (_, Some(_)) => Ok((
+            Command {
+                instruction,
+                output,
+                other,
+            },
+            after,
+        )),
+        _ => Err(spaces(rest)?.1.expected("a register or number")),
+    }
+}
+
 impl FromStr for Command {
-    type Err = std::string::ParseError;
+    type Err = ParseError;
 
     fn from_str(line: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-
-        Ok(Command {
-            instruction: Instruction::from_str(parts[0]).unwrap(),
-            output: parts[1].to_string(),
-            other: parts.get(2).map(|s| s.to_string()),
-        })
+        parse_all(command, line)
     }
 }
 
@@ -216,13 +260,8 @@ fn run_commands_ranges(
     return (minimums, maximums);
 }
 
-fn parse_input(input: &str) -> Vec<Command> {
-    input
-        .trim()
-        .lines()
-        .map(Command::from_str)
-        .map(|c| c.unwrap())
-        .collect()
+fn parse_input(input: &str) -> Result<Vec<Command>, ParseError> {
+    parse_lines(command, input)
 }
 
 fn part_1(commands: &Vec<Command>) -> i64 {
@@ -248,7 +287,8 @@ fn part_2(commands: &Vec<Command>) -> i64 {
 fn main() {
     let start_time = SystemTime::now();
     let args: Vec<String> = env::args().collect();
-    let commands = parse_input(&fs::read_to_string(&args[1]).expect("Could not open input"));
+    let commands = parse_input(&fs::read_to_string(&args[1]).expect("Could not open input"))
+        .unwrap_or_else(|e| panic!("Bad input: {}", e));
 
     let setup_time = SystemTime::now();
 
@@ -267,3 +307,23 @@ fn main() {
         part_2_time.duration_since(start_time).unwrap()
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::parse_input;
+
+    #[test]
+    fn errors() {
+        let position = |input: &str| {
+            let error = parse_input(input).unwrap_err();
+            (error.line, error.column)
+        };
+        assert!(parse_input("inp w\nadd x -12\nmul x y").is_ok());
+        assert_eq!(position("inp w\nadd x 1-2"), (2, 8));
+        assert_eq!(position("mul x --"), (1, 7));
+        assert_eq!(position("inp w\n\nadd wx 1"), (3, 5));
+        assert_eq!(position("inp w 5"), (1, 7));
+        assert_eq!(position("add x"), (1, 6));
+        assert_eq!(position("sub x 1"), (1, 1));
+    }
+}
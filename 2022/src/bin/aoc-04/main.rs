@@ -1,4 +1,5 @@
 #![feature(iter_array_chunks)]
+use aoc::interval::IntervalSet;
 use itertools::Itertools;
 use std::collections::HashSet;
 use std::convert::{TryFrom, TryInto};
@@ -8,21 +9,7 @@ use std::ops::RangeInclusive;
 use std::str::FromStr;
 use std::time::SystemTime;
 
-type Pair = (RangeInclusive<i64>, RangeInclusive<i64>);
-
-fn range_contains_range<T>(a: &RangeInclusive<T>, b: &RangeInclusive<T>) -> bool
-where
-    T: PartialOrd,
-{
-    a.contains(b.start()) && a.contains(b.end())
-}
-
-fn range_overlaps_range<T>(a: &RangeInclusive<T>, b: &RangeInclusive<T>) -> bool
-where
-    T: PartialOrd,
-{
-    a.contains(b.start()) || a.contains(b.end()) || b.contains(a.start()) || b.contains(a.end())
-}
+type Pair = (IntervalSet<i64>, IntervalSet<i64>);
 
 fn parse_input(input: &str) -> Vec<Pair> {
     input
@@ -32,8 +19,12 @@ fn parse_input(input: &str) -> Vec<Pair> {
             let (left_from, left_to) = left.split_once('-').unwrap();
             let (right_from, right_to) = right.split_once('-').unwrap();
             (
-                left_from.parse::<i64>().unwrap()..=left_to.parse::<i64>().unwrap(),
-                right_from.parse::<i64>().unwrap()..=right_to.parse::<i64>().unwrap(),
+                IntervalSet::from(
+                    left_from.parse::<i64>().unwrap()..=left_to.parse::<i64>().unwrap(),
+                ),
+                IntervalSet::from(
+                    right_from.parse::<i64>().unwrap()..=right_to.parse::<i64>().unwrap(),
+                ),
             )
         })
         .collect()
@@ -43,7 +34,7 @@ fn part_1(pairs: &Vec<Pair>) -> usize {
     pairs
         .iter()
         .filter(|(left, right)| {
-            range_contains_range(left, right) || range_contains_range(right, left)
+            left.is_superset(right) || right.is_superset(left)
         })
         .count()
 }
@@ -51,7 +42,7 @@ fn part_1(pairs: &Vec<Pair>) -> usize {
 fn part_2(pairs: &Vec<Pair>) -> usize {
     pairs
         .iter()
-        .filter(|(left, right)| range_overlaps_range(left, right))
+        .filter(|(left, right)| left.overlaps(right))
         .count()
 }
 
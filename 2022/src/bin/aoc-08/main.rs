@@ -1,4 +1,5 @@
 #![feature(array_windows)]
+use aoc::grid::Grid2D;
 use itertools::Itertools;
 use regex::Regex;
 use std::collections::{HashMap, HashSet};
@@ -9,88 +10,55 @@ use std::ops::RangeInclusive;
 use std::str::FromStr;
 use std::time::SystemTime;
 
-fn parse_input(input: &str) -> HashMap<(i64, i64), i64> {
-    input
-        .lines()
-        .enumerate()
-        .map(move |(y, line)| {
-            line.chars().enumerate().map(move |(x, c)| {
-                (
-                    (i64::try_from(x).unwrap(), i64::try_from(y).unwrap()),
-                    c.to_string().parse::<i64>().unwrap(),
-                )
-            })
-        })
-        .flatten()
-        .collect()
+fn parse_input(input: &str) -> Grid2D<i64> {
+    Grid2D::parse_with(input, |c| c.to_string().parse::<i64>()).unwrap()
 }
 
 fn directions() -> Vec<(i64, i64)> {
     vec![(0, 1), (1, 0), (0, -1), (-1, 0)]
 }
 
-fn visible_from_direction(
-    grid: &HashMap<(i64, i64), i64>,
-    pos: &(i64, i64),
-    dir: &(i64, i64),
-) -> bool {
-    let height = grid.get(pos).unwrap();
-    let mut current_pos = *pos;
-    loop {
-        current_pos = (current_pos.0 + dir.0, current_pos.1 + dir.1);
-        match grid.get(&current_pos) {
-            Some(other_height) => {
-                if height <= other_height {
-                    return false;
-                }
-            }
-            None => return true,
-        }
-    }
+fn visible_from_direction(grid: &Grid2D<i64>, pos: (usize, usize), dir: &(i64, i64)) -> bool {
+    let height = grid[pos];
+    grid.ray(pos, *dir).all(|other| height > grid[other])
 }
 
-fn visible_from_any_direction(grid: &HashMap<(i64, i64), i64>, pos: &(i64, i64)) -> bool {
+fn visible_from_any_direction(grid: &Grid2D<i64>, pos: (usize, usize)) -> bool {
     directions()
         .iter()
         .any(|dir| visible_from_direction(grid, pos, dir))
 }
 
-fn viewing_distance(grid: &HashMap<(i64, i64), i64>, pos: &(i64, i64), dir: &(i64, i64)) -> i64 {
-    let height = grid.get(pos).unwrap();
-    let mut current_pos = *pos;
+fn viewing_distance(grid: &Grid2D<i64>, pos: (usize, usize), dir: &(i64, i64)) -> i64 {
+    let height = grid[pos];
     let mut n = 0;
-    loop {
-        current_pos = (current_pos.0 + dir.0, current_pos.1 + dir.1);
+    for other in grid.ray(pos, *dir) {
         n += 1;
-        match grid.get(&current_pos) {
-            Some(other_height) => {
-                if height <= other_height {
-                    return n;
-                }
-            }
-            None => return n - 1,
+        if height <= grid[other] {
+            break;
         }
     }
+    n
 }
 
-fn scenic_score(grid: &HashMap<(i64, i64), i64>, pos: &(i64, i64)) -> i64 {
+fn scenic_score(grid: &Grid2D<i64>, pos: (usize, usize)) -> i64 {
     directions()
         .iter()
         .map(|dir| viewing_distance(grid, pos, dir))
         .product()
 }
 
-fn part_1(grid: &HashMap<(i64, i64), i64>) -> i64 {
-    grid.iter()
-        .filter(|(pos, _)| visible_from_any_direction(grid, pos))
+fn part_1(grid: &Grid2D<i64>) -> i64 {
+    grid.positions()
+        .filter(|pos| visible_from_any_direction(grid, *pos))
         .count()
         .try_into()
         .unwrap()
 }
 
-fn part_2(grid: &HashMap<(i64, i64), i64>) -> i64 {
-    grid.iter()
-        .map(|(pos, _)| scenic_score(grid, pos))
+fn part_2(grid: &Grid2D<i64>) -> i64 {
+    grid.positions()
+        .map(|pos| scenic_score(grid, pos))
         .max()
         .unwrap()
 }
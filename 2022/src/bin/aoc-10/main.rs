@@ -1,4 +1,5 @@
 #![feature(array_windows)]
+use aoc::ocr::recognise_str;
 use itertools::Itertools;
 use regex::Regex;
 use std::collections::{HashMap, HashSet};
@@ -66,7 +67,7 @@ fn part_1(instructions: &Vec<Instruction>) -> i64 {
         .sum()
 }
 
-fn part_2(instructions: &Vec<Instruction>) -> String {
+fn render_screen(instructions: &Vec<Instruction>) -> String {
     let x_history = run_instructions(instructions);
     x_history
         .iter()
@@ -84,6 +85,10 @@ fn part_2(instructions: &Vec<Instruction>) -> String {
         .join("\n")
 }
 
+fn part_2(instructions: &Vec<Instruction>) -> String {
+    recognise_str(&render_screen(instructions)).unwrap()
+}
+
 fn main() {
     let start_time = SystemTime::now();
     let args: Vec<String> = env::args().collect();
@@ -97,7 +102,7 @@ fn main() {
     let part_2_time = SystemTime::now();
 
     println!("Part 1: {:?}", part_1_ans);
-    println!("Part 2:\n{}", part_2_ans);
+    println!("Part 2: {}", part_2_ans);
     println!(
         "\nTime beakdowns:\n\nSetup: {:?}\nPart 1: {:?}\nPart 2: {:?}\nTotal: {:?}",
         setup_time.duration_since(start_time).unwrap(),
@@ -111,7 +116,7 @@ fn main() {
 mod tests {
     use super::parse_input;
     use super::part_1;
-    use super::part_2;
+    use super::render_screen;
     #[test]
     fn example1() {
         let input = "addx 15
@@ -269,6 +274,6 @@ noop";
 #####.....#####.....#####.....#####.....
 ######......######......######......####
 #######.......#######.......#######.....";
-        assert_eq!(part_2(&instructions), part_2_expected);
+        assert_eq!(render_screen(&instructions), part_2_expected);
     }
 }
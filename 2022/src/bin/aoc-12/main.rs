@@ -1,4 +1,5 @@
 #![feature(array_windows)]
+use aoc::search::bfs;
 use itertools::Itertools;
 use regex::Regex;
 use std::collections::{HashMap, HashSet, VecDeque};
@@ -38,34 +39,18 @@ fn parse_input(input: &str) -> (HashMap<(i64, i64), char>, (i64, i64), (i64, i64
 }
 
 fn find_path(grid: &HashMap<(i64, i64), char>, start: (i64, i64), end: (i64, i64)) -> Option<i64> {
-    let mut candidates = VecDeque::new();
-    candidates.push_back((0, start));
-
-    let mut done = HashSet::new();
-
-    while !candidates.is_empty() {
-        let current = candidates.pop_front().unwrap();
-        done.insert(current.1);
-        if current.1 == end {
-            return Some(current.0);
-        }
-        let current_char = grid.get(&current.1).unwrap();
-        for dir in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
-            let next_pos = (current.1 .0 + dir.0, current.1 .1 + dir.1);
-            if done.contains(&next_pos) || candidates.iter().any(|(_, pos)| *pos == next_pos) {
-                continue;
-            }
-            match grid.get(&next_pos) {
-                None => {}
-                Some(next_char) => {
-                    if (*next_char as u32) <= (*current_char as u32) + 1 {
-                        candidates.push_back((current.0 + 1, next_pos));
-                    }
-                }
-            }
-        }
-    }
-    None
+    let successors = |pos: &(i64, i64)| {
+        let current_char = grid[pos];
+        [(0, 1), (1, 0), (0, -1), (-1, 0)]
+            .iter()
+            .map(|dir| (pos.0 + dir.0, pos.1 + dir.1))
+            .filter(|next_pos| match grid.get(next_pos) {
+                Some(next_char) => (*next_char as u32) <= (current_char as u32) + 1,
+                None => false,
+            })
+            .collect::<Vec<_>>()
+    };
+    bfs(vec![start], successors, |pos| *pos == end).map(|path| path.cost.try_into().unwrap())
 }
 
 fn part_1(grid: &HashMap<(i64, i64), char>, start: (i64, i64), end: (i64, i64)) -> i64 {
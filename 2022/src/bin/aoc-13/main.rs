@@ -2,103 +2,176 @@
 use itertools::Itertools;
 use regex::Regex;
 use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, HashSet};
 use std::convert::{TryFrom, TryInto};
 use std::env;
+use std::fmt;
 use std::fs;
 use std::iter::once;
 use std::ops::RangeInclusive;
 use std::str::FromStr;
 use std::time::SystemTime;
 
+use aoc::ordering::sort_with_dividers;
+use aoc::parse::{
+    alt, finish, map, parse_all, preceded, separated, signed, tag, terminated, Input, ParseError,
+    ParseResult,
+};
+
 #[derive(PartialEq, Eq, Clone, Debug)]
 enum Packet {
     Num(i64),
     List(Vec<Packet>),
 }
 
+// Where two packets first differ, and which way
+#[cfg_attr(not(test), allow(dead_code))]
+#[derive(PartialEq, Eq, Clone, Debug)]
+struct Difference {
+    // Indices into nested lists, from the outermost in
+    path: Vec<usize>,
+    ordering: Ordering,
+    reason: String,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "at the top level")?;
+        } else {
+            write!(f, "at ")?;
+            for i in &self.path {
+                write!(f, "[{}]", i)?;
+            }
+        }
+        write!(f, ": {}", self.reason)
+    }
+}
+
+impl Packet {
+    // A number compares as a list holding just itself
+    fn items(&self) -> &[Packet] {
+        match self {
+            Packet::List(items) => items,
+            Packet::Num(_) => std::slice::from_ref(self),
+        }
+    }
+
+    // Compares as the puzzle describes, reporting the first difference found
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn compare(&self, other: &Packet) -> Option<Difference> {
+        self.compare_at(other, &mut Vec::new())
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn compare_at(&self, other: &Packet, path: &mut Vec<usize>) -> Option<Difference> {
+        let difference = |path: &[usize], ordering: Ordering, reason: String| Difference {
+            path: path.to_vec(),
+            ordering,
+            reason,
+        };
+        match (self, other) {
+            (Packet::Num(l), Packet::Num(r)) => match l.cmp(r) {
+                Ordering::Equal => None,
+                Ordering::Less => Some(difference(path, Ordering::Less, format!("{} < {}", l, r))),
+                Ordering::Greater => Some(difference(
+                    path,
+                    Ordering::Greater,
+                    format!("{} > {}", l, r),
+                )),
+            },
+            (Packet::List(_), Packet::Num(_)) => {
+                self.compare_at(&Packet::List(vec![other.clone()]), path)
+            }
+            (Packet::Num(_), Packet::List(_)) => {
+                Packet::List(vec![self.clone()]).compare_at(other, path)
+            }
+            (Packet::List(l), Packet::List(r)) => {
+                for i in 0..l.len().max(r.len()) {
+                    match (l.get(i), r.get(i)) {
+                        (Some(left), Some(right)) => {
+                            path.push(i);
+                            let result = left.compare_at(right, path);
+                            path.pop();
+                            if result.is_some() {
+                                return result;
+                            }
+                        }
+                        (None, _) => {
+                            return Some(difference(
+                                path,
+                                Ordering::Less,
+                                format!("left ran out of items after {}", i),
+                            ))
+                        }
+                        (_, None) => {
+                            return Some(difference(
+                                path,
+                                Ordering::Greater,
+                                format!("right ran out of items after {}", i),
+                            ))
+                        }
+                    }
+                }
+                None
+            }
+        }
+    }
+}
+
 impl PartialOrd for Packet {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
+// The same order as `compare` without building the explanation, as sorting does a lot of these
 impl Ord for Packet {
     fn cmp(&self, other: &Self) -> Ordering {
-        let result = match (self, other) {
+        match (self, other) {
             (Packet::Num(l), Packet::Num(r)) => l.cmp(r),
-            (Packet::List(_), Packet::Num(_)) => self.cmp(&Packet::List(vec![other.clone()])),
-            (Packet::Num(_), Packet::List(_)) => Packet::List(vec![self.clone()]).cmp(other),
-            (Packet::List(l), Packet::List(r)) => {
-                let mut i = 0;
-                loop {
-                    let left = l.get(i);
-                    let right = r.get(i);
-                    i += 1;
-                    if left.is_none() && right.is_none() {
-                        break Ordering::Equal;
-                    } else if left.is_none() {
-                        break Ordering::Less;
-                    } else if right.is_none() {
-                        break Ordering::Greater;
-                    } else if left.unwrap().cmp(right.unwrap()) != Ordering::Equal {
-                        break left.unwrap().cmp(right.unwrap());
-                    } else {
-                        continue;
-                    }
-                }
-            }
-        };
-        return result;
+            _ => self.items().cmp(other.items()),
+        }
+    }
+}
+
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Packet::Num(n) => write!(f, "{}", n),
+            Packet::List(items) => write!(f, "[{}]", items.iter().join(",")),
+        }
     }
 }
 
+fn packet(input: Input) -> ParseResult<Packet> {
+    let num = map(signed, Packet::Num);
+    let empty = map(tag("[]"), |_| Packet::List(Vec::new()));
+    let list = map(
+        preceded(tag("["), terminated(separated(packet, tag(",")), tag("]"))),
+        Packet::List,
+    );
+    alt(num, alt(empty, list))(input)
+}
+
 impl FromStr for Packet {
-    type Err = std::string::ParseError;
+    type Err = ParseError;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        if input.starts_with('[') {
-            let mut list = Vec::new();
-            let mut parts: VecDeque<&str> = input
-                .trim()
-                .strip_prefix("[")
-                .unwrap()
-                .strip_suffix("]")
-                .unwrap()
-                .split(",")
-                .collect();
-
-            // Hacky way of avoiding a stack
-            while !parts.is_empty() {
-                let mut current = parts.pop_front().unwrap().to_string();
-                while current.chars().filter(|c| *c == '[').count()
-                    != current.chars().filter(|c| *c == ']').count()
-                {
-                    current = current + "," + parts.pop_front().unwrap();
-                }
-                if !current.is_empty() {
-                    list.push(current.clone());
-                }
-            }
-
-            return Ok(Packet::List(
-                list.iter().map(|p| Packet::from_str(p).unwrap()).collect(),
-            ));
-        } else {
-            return Ok(Packet::Num(input.parse::<i64>().unwrap()));
-        }
+        parse_all(packet, input)
     }
 }
 
-fn parse_input(input: &str) -> Vec<(Packet, Packet)> {
-    input
-        .split("\n\n")
-        .map(|pair| pair.split_once("\n").unwrap())
-        .map(|(left, right)| {
-            (
-                Packet::from_str(left).unwrap(),
-                Packet::from_str(right).unwrap(),
-            )
+fn parse_input(input: &str) -> Result<Vec<(Packet, Packet)>, ParseError> {
+    Input::new(input)
+        .sections()
+        .into_iter()
+        .map(|section| {
+            let lines = section.lines().collect_vec();
+            if lines.len() != 2 {
+                return Err(section.error(format!("Expected 2 packets, found {}", lines.len())));
+            }
+            Ok((finish(packet, lines[0])?, finish(packet, lines[1])?))
         })
         .collect()
 }
@@ -113,24 +186,23 @@ fn part_1(packets: &Vec<(Packet, Packet)>) -> usize {
 }
 
 fn part_2(packets: &Vec<(Packet, Packet)>) -> usize {
-    let marker_1 = Packet::from_str("[[2]]").unwrap();
-    let marker_2 = Packet::from_str("[[6]]").unwrap();
+    let markers = [
+        Packet::from_str("[[2]]").unwrap(),
+        Packet::from_str("[[6]]").unwrap(),
+    ];
 
-    let mut packets_with_markers = vec![&marker_1, &marker_2];
-    packets_with_markers.extend(packets.iter().flat_map(|(left, right)| [left, right]));
-    packets_with_markers.sort();
-
-    packets_with_markers
+    let all = packets
         .iter()
-        .positions(|&p| p == &marker_1 || p == &marker_2)
-        .map(|p| p + 1)
-        .product()
+        .flat_map(|(left, right)| [left.clone(), right.clone()]);
+    let (_, positions) = sort_with_dividers(all, &markers);
+    positions.iter().product()
 }
 
 fn main() {
     let start_time = SystemTime::now();
     let args: Vec<String> = env::args().collect();
-    let packets = parse_input(&fs::read_to_string(&args[1]).expect("Could not open input"));
+    let packets = parse_input(&fs::read_to_string(&args[1]).expect("Could not open input"))
+        .unwrap_or_else(|e| panic!("Bad input: {}", e));
 
     let setup_time = SystemTime::now();
 
@@ -155,6 +227,10 @@ mod tests {
     use super::parse_input;
     use super::part_1;
     use super::part_2;
+    use super::Packet;
+    use itertools::Itertools;
+    use std::cmp::Ordering;
+    use std::str::FromStr;
     #[test]
     fn example1() {
         let input = "[1,1,3,1,1]
@@ -180,8 +256,65 @@ mod tests {
 
 [1,[2,[3,[4,[5,6,7]]]],8,9]
 [1,[2,[3,[4,[5,6,0]]]],8,9]";
-        let packets = parse_input(input);
+        let packets = parse_input(input).unwrap();
         assert_eq!(part_1(&packets), 13);
         assert_eq!(part_2(&packets), 140);
+
+        // Printing gives back exactly what was read
+        let printed = packets
+            .iter()
+            .map(|(left, right)| format!("{}\n{}", left, right))
+            .join("\n\n");
+        assert_eq!(printed, input);
+
+        // Sorting's comparison agrees with the explained one on every pair
+        let all = packets.iter().flat_map(|(l, r)| vec![l, r]).collect_vec();
+        for a in all.iter() {
+            for b in all.iter() {
+                let explained = a.compare(b).map_or(Ordering::Equal, |d| d.ordering);
+                assert_eq!(a.cmp(b), explained);
+            }
+        }
+    }
+
+    #[test]
+    fn explanations() {
+        let explain = |left: &str, right: &str| {
+            let left = Packet::from_str(left).unwrap();
+            let right = Packet::from_str(right).unwrap();
+            left.compare(&right).map(|d| (d.ordering, d.to_string()))
+        };
+        assert_eq!(
+            explain("[1,1,3,1,1]", "[1,1,5,1,1]"),
+            Some((Ordering::Less, "at [2]: 3 < 5".to_string()))
+        );
+        assert_eq!(
+            explain("[[1],[2,3,4]]", "[[1],4]"),
+            Some((Ordering::Less, "at [1][0]: 2 < 4".to_string()))
+        );
+        assert_eq!(
+            explain("[[[]]]", "[[]]"),
+            Some((
+                Ordering::Greater,
+                "at [0]: right ran out of items after 0".to_string()
+            ))
+        );
+        assert_eq!(
+            explain("[7,7,7,7]", "[7,7,7]"),
+            Some((
+                Ordering::Greater,
+                "at the top level: right ran out of items after 3".to_string()
+            ))
+        );
+        assert_eq!(explain("[1,[2]]", "[[1],2]"), None);
+    }
+
+    #[test]
+    fn errors() {
+        let error = Packet::from_str("[1,[2,x]]").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 7));
+        let error = parse_input("[1]\n[2]\n\n[3]\n[4,]").unwrap_err();
+        assert_eq!((error.line, error.column), (5, 4));
+        assert!(parse_input("[1]\n[2]\n[3]").is_err());
     }
 }
@@ -1,4 +1,5 @@
 #![feature(array_windows)]
+use aoc::interval::IntervalSet;
 use itertools::Itertools;
 use regex::Regex;
 use std::cmp;
@@ -81,18 +82,20 @@ fn parse_input(input: &str) -> Vec<Sensor> {
 }
 
 fn part_1(sensors: &Vec<Sensor>, target_y: i64) -> i64 {
-    sensors
+    let coverage: IntervalSet<i64> = sensors
         .iter()
         .map(|sensor| sensor.coverage_for_line(target_y))
-        .sorted_by_key(|coverage| *coverage.start())
-        .fold((0, i64::MIN), |(total, position), coverage| {
-            let start = position.max(*coverage.start());
-            let end = position.max(*coverage.end());
-            let distance = 0.max(end - start);
+        .collect();
 
-            (total + distance, end)
-        })
-        .0
+    let beacons = sensors
+        .iter()
+        .filter(|sensor| sensor.beacon.1 == target_y)
+        .map(|sensor| sensor.beacon.0)
+        .unique()
+        .filter(|x| coverage.contains(*x))
+        .count();
+
+    coverage.len() - i64::try_from(beacons).unwrap()
 }
 
 fn rotate(pos: (i64, i64)) -> (i64, i64) {
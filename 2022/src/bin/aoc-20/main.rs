@@ -1,11 +1,10 @@
-#![feature(array_windows)]
-#![feature(linked_list_cursors)]
+use aoc::ring::Ring;
 use itertools::Itertools;
 use regex::Regex;
 use std::cmp;
 use std::cmp::max;
 use std::cmp::Ordering;
-use std::collections::{BTreeSet, HashMap, HashSet, LinkedList, VecDeque};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::convert::{TryFrom, TryInto};
 use std::env;
 use std::fs;
@@ -22,85 +21,32 @@ fn parse_input(input: &str) -> Vec<i64> {
         .collect()
 }
 
-fn print_list(list: &LinkedList<(i64, i64)>) {
-    println!("List:");
-    list.iter().for_each(|(_, value)| print!("{:},", value));
-    println!("");
-}
-
-fn mix(list: &mut LinkedList<(i64, i64)>) {
-    let length = list.len() as i64;
-    for index in 0..length {
-        let mut cursor = list.cursor_front_mut();
-        while cursor.current().map(|num| num.0) != Some(index) {
-            cursor.move_next();
-        }
-
-        let current = cursor.remove_current().unwrap();
-        if cursor.current() == None {
-            cursor.move_next();
-        }
-
-        let to_move = current.1.abs() % (length - 1);
-        if current.1 > 0 {
-            for _ in 0..to_move {
-                cursor.move_next();
-                if cursor.current() == None {
-                    cursor.move_next();
-                }
-            }
-        } else if current.1 < 0 {
-            for _ in 0..to_move {
-                cursor.move_prev();
-                if cursor.current() == None {
-                    cursor.move_prev();
-                }
-            }
-        }
-
-        cursor.insert_before(current);
+fn mix(ring: &mut Ring<i64>, nums: &[i64]) {
+    for (id, value) in nums.iter().enumerate() {
+        ring.move_by(id, *value);
     }
 }
 
-fn score(list: &LinkedList<(i64, i64)>) -> i64 {
-    let mut cursor = list.cursor_front();
-    while cursor.current().map(|num| num.1) != Some(0) {
-        cursor.move_next();
-    }
-
-    let mut sum = 0;
-    for _ in 0..3 {
-        for _ in 0..1000 {
-            cursor.move_next();
-            if cursor.current() == None {
-                cursor.move_next();
-            }
-        }
-        sum += cursor.current().unwrap().1;
-    }
-    sum
+fn score(ring: &Ring<i64>) -> i64 {
+    let zero = ring.find(&0).unwrap();
+    (1..=3)
+        .map(|n| *ring.value(ring.step(zero, n * 1000)))
+        .sum()
 }
 
 fn part_1(nums: &Vec<i64>) -> i64 {
-    let mut list = LinkedList::new();
-    for (index, value) in nums.iter().enumerate() {
-        list.push_back((index as i64, *value));
-    }
-
-    mix(&mut list);
-    score(&list)
+    let mut ring: Ring<i64> = nums.iter().cloned().collect();
+    mix(&mut ring, nums);
+    score(&ring)
 }
 
 fn part_2(nums: &Vec<i64>) -> i64 {
-    let mut list = LinkedList::new();
-    for (index, value) in nums.iter().enumerate() {
-        list.push_back((index as i64, *value * 811589153));
-    }
-
+    let nums = nums.iter().map(|value| value * 811589153).collect_vec();
+    let mut ring: Ring<i64> = nums.iter().cloned().collect();
     for _ in 1..=10 {
-        mix(&mut list);
+        mix(&mut ring, &nums);
     }
-    score(&list)
+    score(&ring)
 }
 
 fn main() {
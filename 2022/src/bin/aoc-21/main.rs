@@ -1,5 +1,9 @@
 #![feature(array_windows)]
 #![feature(linked_list_cursors)]
+use aoc::parse::{
+    alt, map, one_of, pair, parse_all, parse_lines, signed, tag, terminated, word, ws, Input,
+    ParseError, ParseResult,
+};
 use itertools::Itertools;
 use regex::Regex;
 use std::cmp;
@@ -80,36 +84,34 @@ impl Monkey {
     }
 }
 
+fn monkey(input: Input) -> ParseResult<Monkey> {
+    let constant = map(signed, |val| Value::Constant(val as f64));
+    let operation = map(
+        pair(word, pair(ws(one_of("+-*/")), ws(word))),
+        |(id_1, (op, id_2))| Value::Operation(id_1.to_string(), op, id_2.to_string()),
+    );
+    map(
+        pair(terminated(word, tag(": ")), alt(constant, operation)),
+        |(id, value)| Monkey {
+            id: id.to_string(),
+            value,
+        },
+    )(input)
+}
+
 impl FromStr for Monkey {
-    type Err = std::string::ParseError;
+    type Err = ParseError;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let (id, parts) = input.split_once(": ").unwrap();
-        if let Ok(val) = parts.parse::<f64>() {
-            Ok(Monkey {
-                id: id.to_string(),
-                value: Value::Constant(val),
-            })
-        } else {
-            let (id_1, op, id_2) = parts.split(" ").collect_tuple().unwrap();
-            Ok(Monkey {
-                id: id.to_string(),
-                value: Value::Operation(
-                    id_1.to_string(),
-                    op.chars().nth(0).unwrap(),
-                    id_2.to_string(),
-                ),
-            })
-        }
+        parse_all(monkey, input)
     }
 }
 
-fn parse_input(input: &str) -> HashMap<String, Monkey> {
-    input
-        .lines()
-        .map(|line| line.parse::<Monkey>().unwrap())
+fn parse_input(input: &str) -> Result<HashMap<String, Monkey>, ParseError> {
+    Ok(parse_lines(monkey, input)?
+        .into_iter()
         .map(|monkey| (monkey.id.clone(), monkey))
-        .collect()
+        .collect())
 }
 
 fn part_1(monkeys: &HashMap<String, Monkey>) -> i64 {
@@ -143,7 +145,8 @@ fn part_2(monkeys: &HashMap<String, Monkey>) -> i64 {
 fn main() {
     let start_time = SystemTime::now();
     let args: Vec<String> = env::args().collect();
-    let valves = parse_input(&fs::read_to_string(&args[1]).expect("Could not open input"));
+    let valves = parse_input(&fs::read_to_string(&args[1]).expect("Could not open input"))
+        .unwrap_or_else(|e| panic!("Bad input: {}", e));
 
     let setup_time = SystemTime::now();
 
@@ -185,8 +188,11 @@ pppw: cczh / lfqf
 lgvd: ljgn * ptdq
 drzm: hmdt - zczc
 hmdt: 32";
-        let monkeys = parse_input(input);
+        let monkeys = parse_input(input).unwrap();
         assert_eq!(part_1(&monkeys), 152);
         assert_eq!(part_2(&monkeys), 301);
+
+        let error = parse_input("root: pppw + sjmn\ndbpl: 5\ncczh: sllz % lgvd").unwrap_err();
+        assert_eq!((error.line, error.column), (3, 12));
     }
 }
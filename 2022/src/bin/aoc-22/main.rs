@@ -15,6 +15,8 @@ use std::ops::RangeInclusive;
 use std::str::FromStr;
 use std::time::SystemTime;
 
+use aoc::point::Point3;
+
 type Pos = (i64, i64);
 
 #[derive(Debug, Clone, Hash, Copy, Eq, PartialEq)]
@@ -174,66 +176,137 @@ fn next_existing(current: (i64, i64), vector: (i64, i64), map: &HashMap<Pos, cha
     }
 }
 
-fn next_existing_3d(
-    current: &State,
-    map: &HashMap<Pos, char>,
-    portals: &HashMap<i64, HashMap<Facing, (i64, Vec<Instruction>)>>,
-    map_areas: &HashMap<i64, Pos>,
-    side_length: &i64,
-) -> State {
-    let vector = current.facing.vector();
-    let pos = (current.pos.0 + vector.0, current.pos.1 + vector.1);
-    if map.get(&pos).is_some() {
-        State {
-            pos,
-            facing: current.facing,
+// How a face of the net sits on the cube: the directions its x and y axes point in, and the
+// direction out of the cube through it
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Face {
+    right: Point3,
+    down: Point3,
+    normal: Point3,
+}
+
+impl Face {
+    fn vector(&self, facing: Facing) -> Point3 {
+        match facing {
+            Facing::North => -self.down,
+            Facing::East => self.right,
+            Facing::South => self.down,
+            Facing::West => -self.right,
         }
-    } else {
-        let map_tile = (current.pos.0 / side_length, current.pos.1 / side_length);
-        let current_map_area = map_areas
-            .iter()
-            .find(|(_, a)| **a == map_tile)
-            .unwrap()
-            .0
-            .clone();
-
-        let (next_map_area, instructions) = &portals[&current_map_area][&current.facing];
-
-        let mut next_facing = current.facing;
-        let mut next_pos = (
-            pos.0 - map_areas[&current_map_area].0 * side_length,
-            pos.1 - map_areas[&current_map_area].1 * side_length,
-        );
-
-        for instruction in instructions {
-            match instruction {
-                Instruction::Left => {
-                    next_facing = next_facing.left();
-                    next_pos = (next_pos.1, (side_length - 1) - next_pos.0);
-                }
-                Instruction::Right => {
-                    next_facing = next_facing.right();
-                    next_pos = ((side_length - 1) - next_pos.1, next_pos.0);
+    }
+
+    // The face on the other side of the net edge in direction `facing`, once folded
+    fn fold(&self, facing: Facing) -> Face {
+        let outwards = self.vector(facing);
+        let (right, down) = match facing {
+            Facing::North | Facing::South => (self.right, -self.normal * outwards.dot(&self.down)),
+            Facing::East | Facing::West => (-self.normal * outwards.dot(&self.right), self.down),
+        };
+        Face {
+            right,
+            down,
+            normal: outwards,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Cube {
+    side_length: i64,
+    // Keyed by the position of the face in the net, in units of side_length
+    faces: HashMap<Pos, Face>,
+}
+
+impl Cube {
+    // Works out how the net folds up by walking across it from the first face
+    fn fold(map: &HashMap<Pos, char>) -> Result<Cube, String> {
+        let side_length = (1..)
+            .find(|s| 6 * s * s >= map.len())
+            .filter(|s| 6 * s * s == map.len())
+            .ok_or_else(|| format!("{} tiles can't make a cube", map.len()))?
+            as i64;
+
+        let tiles: HashSet<Pos> = map
+            .keys()
+            .map(|(x, y)| (x / side_length, y / side_length))
+            .collect();
+        let start = *tiles.iter().min_by_key(|(x, y)| (*y, *x)).unwrap();
+
+        let mut faces = HashMap::from([(
+            start,
+            Face {
+                right: Point3::new(1, 0, 0),
+                down: Point3::new(0, 1, 0),
+                normal: Point3::new(0, 0, -1),
+            },
+        )]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(tile) = queue.pop_front() {
+            let face = faces[&tile];
+            for facing in [Facing::North, Facing::East, Facing::South, Facing::West] {
+                let vector = facing.vector();
+                let next = (tile.0 + vector.0, tile.1 + vector.1);
+                if tiles.contains(&next) && !faces.contains_key(&next) {
+                    faces.insert(next, face.fold(facing));
+                    queue.push_back(next);
                 }
-                _ => panic!("Unexpected instruction: {:?}", instruction),
             }
         }
-        match next_facing {
-            Facing::North => next_pos.1 += side_length,
-            Facing::East => next_pos.0 -= side_length,
-            Facing::South => next_pos.1 -= side_length,
-            Facing::West => next_pos.0 += side_length,
+
+        let normals: HashSet<Point3> = faces.values().map(|f| f.normal).collect();
+        if faces.len() != 6 || normals.len() != 6 {
+            return Err(format!("Map is not the net of a cube: {:?}", tiles));
         }
+        Ok(Cube { side_length, faces })
+    }
+
+    // Cell centres are placed on a cube spanning -side_length..=side_length in doubled
+    // coordinates so that they all land on integers
+    fn to_3d(&self, pos: Pos) -> (Point3, &Face) {
+        let s = self.side_length;
+        let face = &self.faces[&(pos.0 / s, pos.1 / s)];
+        let (x, y) = (pos.0 % s, pos.1 % s);
+        let centre = face.normal * s + face.right * (2 * x + 1 - s) + face.down * (2 * y + 1 - s);
+        (centre, face)
+    }
+
+    // Steps off the edge of one face onto the face it is glued to
+    fn wrap(&self, current: &State) -> State {
+        let s = self.side_length;
+        let (centre, face) = self.to_3d(current.pos);
+        let outwards = face.vector(current.facing);
+        let next = centre + outwards - face.normal;
+
+        let (tile, next_face) = self
+            .faces
+            .iter()
+            .find(|(_, f)| f.normal == outwards)
+            .unwrap();
+        let facing = [Facing::North, Facing::East, Facing::South, Facing::West]
+            .iter()
+            .copied()
+            .find(|f| next_face.vector(*f) == -face.normal)
+            .unwrap();
+        let x = (next.dot(&next_face.right) + s - 1) / 2;
+        let y = (next.dot(&next_face.down) + s - 1) / 2;
 
-        next_pos = (
-            next_pos.0 + map_areas[&next_map_area].0 * side_length,
-            next_pos.1 + map_areas[&next_map_area].1 * side_length,
-        );
+        State {
+            pos: (tile.0 * s + x, tile.1 * s + y),
+            facing,
+        }
+    }
+}
 
+fn next_existing_3d(current: &State, map: &HashMap<Pos, char>, cube: &Cube) -> State {
+    let vector = current.facing.vector();
+    let pos = (current.pos.0 + vector.0, current.pos.1 + vector.1);
+    if map.get(&pos).is_some() {
         State {
-            pos: next_pos,
-            facing: next_facing,
+            pos,
+            facing: current.facing,
         }
+    } else {
+        cube.wrap(current)
     }
 }
 
@@ -272,14 +345,7 @@ impl State {
         }
     }
 
-    fn next_3d(
-        &self,
-        map: &HashMap<Pos, char>,
-        instruction: &Instruction,
-        portals: &HashMap<i64, HashMap<Facing, (i64, Vec<Instruction>)>>,
-        map_areas: &HashMap<i64, Pos>,
-        side_length: &i64,
-    ) -> State {
+    fn next_3d(&self, map: &HashMap<Pos, char>, instruction: &Instruction, cube: &Cube) -> State {
         match instruction {
             Instruction::Left => State {
                 pos: self.pos,
@@ -292,7 +358,7 @@ impl State {
             Instruction::Advance(distance) => {
                 let mut current = self.clone();
                 for _ in 0..*distance {
-                    let next = next_existing_3d(&current, map, portals, map_areas, side_length);
+                    let next = next_existing_3d(&current, map, cube);
                     match map.get(&next.pos).unwrap() {
                         '.' => current = next,
                         '#' => break,
@@ -323,262 +389,8 @@ fn part_1(map: &HashMap<Pos, char>, route: &Vec<Instruction>) -> i64 {
     1000 * (state.pos.1 + 1) + 4 * (state.pos.0 + 1) + state.facing.score()
 }
 
-fn test_map() -> (
-    i64,
-    HashMap<i64, Pos>,
-    HashMap<i64, HashMap<Facing, (i64, Vec<Instruction>)>>,
-) {
-    /*
-            1111
-            1111
-            1111
-            1111
-    222233334444
-    222233334444
-    222233334444
-    222233334444
-            55556666
-            55556666
-            55556666
-            55556666
-    */
-
-    let map_areas = HashMap::from([
-        (1, (2, 0)),
-        (2, (0, 1)),
-        (3, (1, 1)),
-        (4, (2, 1)),
-        (5, (2, 2)),
-        (6, (3, 2)),
-    ]);
-    let mut portals: HashMap<i64, HashMap<Facing, (i64, Vec<Instruction>)>> = HashMap::new();
-    portals.insert(
-        1,
-        HashMap::from([
-            (
-                Facing::North,
-                (2, vec![Instruction::Left, Instruction::Left]),
-            ),
-            (
-                Facing::East,
-                (6, vec![Instruction::Right, Instruction::Right]),
-            ),
-            (Facing::South, (4, vec![])),
-            (Facing::West, (3, vec![Instruction::Left])),
-        ]),
-    );
-    portals.insert(
-        2,
-        HashMap::from([
-            (
-                Facing::North,
-                (1, vec![Instruction::Right, Instruction::Right]),
-            ),
-            (Facing::East, (3, vec![])),
-            (
-                Facing::South,
-                (5, vec![Instruction::Left, Instruction::Left]),
-            ),
-            (
-                Facing::West,
-                (
-                    6,
-                    vec![Instruction::Left, Instruction::Left, Instruction::Left],
-                ),
-            ),
-        ]),
-    );
-    portals.insert(
-        3,
-        HashMap::from([
-            (Facing::North, (1, vec![Instruction::Right])),
-            (Facing::East, (4, vec![])),
-            (Facing::South, (5, vec![Instruction::Left])),
-            (Facing::West, (2, vec![])),
-        ]),
-    );
-    portals.insert(
-        4,
-        HashMap::from([
-            (Facing::North, (1, vec![])),
-            (Facing::East, (6, vec![Instruction::Right])),
-            (Facing::South, (5, vec![])),
-            (Facing::West, (3, vec![])),
-        ]),
-    );
-    portals.insert(
-        5,
-        HashMap::from([
-            (Facing::North, (4, vec![])),
-            (Facing::East, (6, vec![])),
-            (
-                Facing::South,
-                (2, vec![Instruction::Right, Instruction::Right]),
-            ),
-            (Facing::West, (3, vec![Instruction::Right])),
-        ]),
-    );
-    portals.insert(
-        6,
-        HashMap::from([
-            (Facing::North, (4, vec![Instruction::Left])),
-            (
-                Facing::East,
-                (1, vec![Instruction::Left, Instruction::Left]),
-            ),
-            (
-                Facing::South,
-                (
-                    2,
-                    vec![Instruction::Right, Instruction::Right, Instruction::Right],
-                ),
-            ),
-            (Facing::West, (5, vec![])),
-        ]),
-    );
-
-    (4, map_areas, portals)
-}
-
-fn real_map() -> (
-    i64,
-    HashMap<i64, Pos>,
-    HashMap<i64, HashMap<Facing, (i64, Vec<Instruction>)>>,
-) {
-    /*
-        11112222
-        11112222
-        11112222
-        11112222
-        3333
-        3333
-        3333
-        3333
-    44445555
-    44445555
-    44445555
-    44445555
-    6666
-    6666
-    6666
-    6666
-    */
-
-    let map_areas = HashMap::from([
-        (1, (1, 0)),
-        (2, (2, 0)),
-        (3, (1, 1)),
-        (4, (0, 2)),
-        (5, (1, 2)),
-        (6, (0, 3)),
-    ]);
-    let mut portals: HashMap<i64, HashMap<Facing, (i64, Vec<Instruction>)>> = HashMap::new();
-    portals.insert(
-        1,
-        HashMap::from([
-            (
-                Facing::North,
-                (
-                    6,
-                    vec![Instruction::Left, Instruction::Left, Instruction::Left],
-                ),
-            ),
-            (Facing::East, (2, vec![])),
-            (Facing::South, (3, vec![])),
-            (
-                Facing::West,
-                (4, vec![Instruction::Left, Instruction::Left]),
-            ),
-        ]),
-    );
-    portals.insert(
-        2,
-        HashMap::from([
-            (
-                Facing::North,
-                (
-                    6,
-                    vec![
-                        Instruction::Right,
-                        Instruction::Right,
-                        Instruction::Right,
-                        Instruction::Right,
-                    ],
-                ),
-            ),
-            (
-                Facing::East,
-                (5, vec![Instruction::Right, Instruction::Right]),
-            ),
-            (Facing::South, (3, vec![Instruction::Right])),
-            (Facing::West, (1, vec![])),
-        ]),
-    );
-    portals.insert(
-        3,
-        HashMap::from([
-            (Facing::North, (1, vec![])),
-            (Facing::East, (2, vec![Instruction::Left])),
-            (Facing::South, (5, vec![])),
-            (Facing::West, (4, vec![Instruction::Left])),
-        ]),
-    );
-    portals.insert(
-        4,
-        HashMap::from([
-            (Facing::North, (3, vec![Instruction::Right])),
-            (Facing::East, (5, vec![])),
-            (Facing::South, (6, vec![])),
-            (
-                Facing::West,
-                (1, vec![Instruction::Right, Instruction::Right]),
-            ),
-        ]),
-    );
-    portals.insert(
-        5,
-        HashMap::from([
-            (Facing::North, (3, vec![])),
-            (
-                Facing::East,
-                (2, vec![Instruction::Left, Instruction::Left]),
-            ),
-            (Facing::South, (6, vec![Instruction::Right])),
-            (Facing::West, (4, vec![])),
-        ]),
-    );
-    portals.insert(
-        6,
-        HashMap::from([
-            (Facing::North, (4, vec![])),
-            (Facing::East, (5, vec![Instruction::Left])),
-            (
-                Facing::South,
-                (
-                    2,
-                    vec![
-                        Instruction::Left,
-                        Instruction::Left,
-                        Instruction::Left,
-                        Instruction::Left,
-                    ],
-                ),
-            ),
-            (
-                Facing::West,
-                (
-                    1,
-                    vec![Instruction::Right, Instruction::Right, Instruction::Right],
-                ),
-            ),
-        ]),
-    );
-
-    (50, map_areas, portals)
-}
-
-fn part_2(map: &HashMap<Pos, char>, route: &Vec<Instruction>, test: bool) -> i64 {
-    let (side_length, map_areas, portals) = if test { test_map() } else { real_map() };
+fn part_2(map: &HashMap<Pos, char>, route: &Vec<Instruction>) -> i64 {
+    let cube = Cube::fold(map).unwrap();
 
     let mut state = State {
         pos: map
@@ -591,7 +403,7 @@ fn part_2(map: &HashMap<Pos, char>, route: &Vec<Instruction>, test: bool) -> i64
         facing: Facing::East,
     };
     for instruction in route {
-        state = state.next_3d(map, instruction, &portals, &map_areas, &side_length);
+        state = state.next_3d(map, instruction, &cube);
     }
 
     1000 * (state.pos.1 + 1) + 4 * (state.pos.0 + 1) + state.facing.score()
@@ -606,7 +418,7 @@ fn main() {
 
     let part_1_ans = part_1(&map, &route);
     let part_1_time = SystemTime::now();
-    let part_2_ans = part_2(&map, &route, false);
+    let part_2_ans = part_2(&map, &route);
     let part_2_time = SystemTime::now();
 
     println!("Part 1: {:?}", part_1_ans);
@@ -625,6 +437,8 @@ mod tests {
     use super::parse_input;
     use super::part_1;
     use super::part_2;
+    use super::{next_existing_3d, Cube, Facing, State};
+    use std::collections::HashMap;
     #[test]
     fn example1() {
         let input = "        ...#
@@ -643,6 +457,75 @@ mod tests {
 10R5L5R10L4R5L5";
         let (map, route) = parse_input(input);
         assert_eq!(part_1(&map, &route), 6032);
-        assert_eq!(part_2(&map, &route, true), 5031);
+        assert_eq!(part_2(&map, &route), 5031);
+    }
+
+    #[test]
+    fn every_net() {
+        let nets = [
+            "#...\n####\n#...",
+            "#...\n####\n.#..",
+            "#...\n####\n..#.",
+            "#...\n####\n...#",
+            ".#..\n####\n.#..",
+            ".#..\n####\n..#.",
+            "##..\n.###\n.#..",
+            "##..\n.###\n..#.",
+            "##..\n.###\n...#",
+            "##..\n.##.\n..##",
+            "###..\n..###",
+        ];
+        let side_length = 3;
+        for net in nets {
+            let map: HashMap<(i64, i64), char> = net
+                .lines()
+                .enumerate()
+                .flat_map(|(y, line)| {
+                    line.char_indices()
+                        .filter(|(_, c)| *c == '#')
+                        .map(move |(x, _)| (x as i64, y as i64))
+                })
+                .flat_map(|(x, y)| {
+                    (0..side_length).flat_map(move |dy| {
+                        (0..side_length)
+                            .map(move |dx| ((x * side_length + dx, y * side_length + dy), '.'))
+                    })
+                })
+                .collect();
+            let cube = Cube::fold(&map).unwrap();
+
+            // Walking all the way round the cube comes back to the start, and turning round after
+            // crossing any edge leads straight back
+            for pos in map.keys() {
+                for facing in [Facing::North, Facing::East, Facing::South, Facing::West] {
+                    let start = State { pos: *pos, facing };
+                    let mut current = start.clone();
+                    for _ in 0..4 * side_length {
+                        current = next_existing_3d(&current, &map, &cube);
+                    }
+                    assert_eq!(
+                        (current.pos, current.facing),
+                        (start.pos, start.facing),
+                        "{}",
+                        net
+                    );
+
+                    let next = next_existing_3d(&start, &map, &cube);
+                    let back = State {
+                        pos: next.pos,
+                        facing: next.facing.left().left(),
+                    };
+                    assert_eq!(
+                        next_existing_3d(&back, &map, &cube).pos,
+                        start.pos,
+                        "{}",
+                        net
+                    );
+                }
+            }
+        }
+
+        let strip: HashMap<(i64, i64), char> = (0..6).map(|x| ((x, 0), '.')).collect();
+        assert!(Cube::fold(&strip).is_err());
     }
 }
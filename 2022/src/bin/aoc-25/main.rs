@@ -1,6 +1,6 @@
 #![feature(array_windows)]
 #![feature(linked_list_cursors)]
-use itertools::Itertools;
+use aoc::balanced::{Balanced, Radix};
 use regex::Regex;
 use std::cmp;
 use std::cmp::max;
@@ -15,58 +15,23 @@ use std::ops::RangeInclusive;
 use std::str::FromStr;
 use std::time::SystemTime;
 
-fn parse_input(input: &str) -> Vec<String> {
-    input.lines().map(|s| s.to_string()).collect()
+fn parse_input(input: &str) -> Result<Vec<Balanced>, String> {
+    input.lines().map(|line| Radix::SNAFU.parse(line)).collect()
 }
 
-fn from_snafu(snafu: &String) -> i64 {
-    let mut total = 0;
-    for c in snafu.trim().chars() {
-        total *= 5;
-        total += match c {
-            '2' => 2,
-            '1' => 1,
-            '0' => 0,
-            '-' => -1,
-            '=' => -2,
-            _ => panic!("unexpected char: {:?}", c),
-        }
-    }
-    total
-}
-
-fn to_snafu(num: &i64) -> String {
-    let mut initial_power = 0;
-    while 5i64.pow(initial_power) < *num {
-        initial_power += 1;
-    }
-    let mut digits = vec!['0'; initial_power as usize];
-    for d in 0..digits.len() {
-        let options = ['2', '1', '0', '-', '='];
-        let next = options
-            .iter()
-            .min_by_key(|option| {
-                let mut candidate = digits.clone();
-                candidate[d] = **option;
-                let candidate_str = candidate.iter().join("");
-                (num - from_snafu(&candidate_str)).abs()
-            })
-            .unwrap();
-        digits[d] = *next;
-    }
-    let result = digits.iter().join("");
-    result
-}
-
-fn part_1(numbers: &Vec<String>) -> String {
-    let total = numbers.iter().map(from_snafu).sum();
-    to_snafu(&total)
+fn part_1(numbers: &[Balanced]) -> String {
+    numbers
+        .iter()
+        .cloned()
+        .fold(Radix::SNAFU.zero(), |total, n| total + n)
+        .to_string()
 }
 
 fn main() {
     let start_time = SystemTime::now();
     let args: Vec<String> = env::args().collect();
-    let numbers = parse_input(&fs::read_to_string(&args[1]).expect("Could not open input"));
+    let numbers = parse_input(&fs::read_to_string(&args[1]).expect("Could not open input"))
+        .unwrap_or_else(|e| panic!("Bad input: {}", e));
 
     let setup_time = SystemTime::now();
 
@@ -106,7 +71,7 @@ mod tests {
     12
     1=
    122";
-        let numbers = parse_input(input);
+        let numbers = parse_input(input).unwrap();
         assert_eq!(part_1(&numbers), "2=-1=0".to_string());
         // assert_eq!(part_2(&walls, &blizzards), 54);
     }
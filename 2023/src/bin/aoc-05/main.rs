@@ -9,6 +9,7 @@ use std::str::FromStr;
 use std::ops::Range;
 use num::abs;
 use regex::Regex;
+use aoc::interval::IntervalSet;
 
 #[derive(Copy, Clone, Debug)]
 struct Mapping {
@@ -27,68 +28,12 @@ impl Mapping {
         }
     }
 
-    fn apply_range(self, nums: &Range<i64>) -> (Vec<Range<i64>>, Vec<Range<i64>>) {
-        if nums.start < self.source {
-            // start...end...|...|
-            if nums.end < self.source {
-                (
-                    vec![Range{start: nums.start, end: nums.end}],
-                    vec![],
-                )
-            }
-            // start...|...end...|
-            else if nums.end >= self.source && nums.end < self.source + self.length {
-                (
-                    vec![
-                        Range{start: nums.start, end: self.source},
-                    ],
-                    vec![
-                        Range{start: self.dest, end: self.dest + nums.end - self.source},
-                    ],
-                )
-            }
-            // start...|...|...end
-            else {
-                (
-                    vec![
-                        Range{start: nums.start, end: self.source},
-                        Range{start: self.source + self.length, end: nums.end},
-                    ],
-                    vec![
-                        Range{start: self.dest, end: self.dest + self.length},
-                    ],
-                )
-            }
-        }
-        else if nums.start >= self.source && nums.start < self.source + self.length {
-            // |...start...end...|
-            if nums.end < self.source + self.length {
-                (
-                    vec![],
-                    vec![
-                        Range{start: self.dest + (nums.start - self.source), end: self.dest + (nums.end - self.source)},
-                    ],
-                )
-            }
-            // |...start...|...end
-            else {
-                (
-                    vec![
-                        Range{start: self.source + self.length, end: nums.end},
-                    ],
-                    vec![
-                        Range{start: self.dest + (nums.start - self.source), end: self.dest + (nums.end - self.source)},
-                    ],
-                )
-            }
-        }
-        // |...|...start...end
-        else {
-            (
-                vec![Range{start: nums.start, end: nums.end}],
-                vec![],
-            )
-        }
+    fn apply_set(self, nums: &IntervalSet<i64>) -> (IntervalSet<i64>, IntervalSet<i64>) {
+        let source = IntervalSet::from(self.source..self.source + self.length);
+        (
+            nums.difference(&source),
+            nums.intersection(&source).offset(self.dest - self.source),
+        )
     }
 }
 
@@ -159,35 +104,24 @@ fn part_2((seeds, maps): &(Vec<i64>, HashMap<String, (String, Vec<Mapping>)>)) -
     seeds.chunks_exact(2)
         .map(|pair| {
             if let [start, length] = pair {
-                let mut nums = vec![Range{start: *start, end: (start + length)}];
+                let mut nums = IntervalSet::from(*start..(start + length));
                 let mut current_type = "seed";
                 while current_type != "location" {
                     let current_map = &maps[current_type];
                     let current_ranges = &current_map.1;
 
                     let mut remaining_nums = nums;
-                    let mut next_nums = vec![];
+                    let mut next_nums = IntervalSet::new();
                     current_ranges.iter()
                         .for_each(|current_range| {
-                            let mut next_remaining = vec![];
-                            remaining_nums.iter()
-                                .for_each(|num| {
-                                    let (mut remaining, mut next) = current_range.apply_range(num);
-                                    next_remaining.append(&mut remaining);
-                                    next_nums.append(&mut next);
-                                });
-                            remaining_nums = next_remaining;
+                            let (remaining, next) = current_range.apply_set(&remaining_nums);
+                            remaining_nums = remaining;
+                            next_nums = next_nums.union(&next);
                         });
                     current_type = current_map.0.as_str();
-                    nums = next_nums;
-                    nums.append(&mut remaining_nums);
-                    nums.retain(|num| !num.is_empty());
+                    nums = next_nums.union(&remaining_nums);
                 }
-                let min = nums.iter()
-                    .map(|n| n.start)
-                    .min()
-                    .unwrap();
-                min
+                nums.min().unwrap()
             }
             else {
                 panic!("seeds not paired");
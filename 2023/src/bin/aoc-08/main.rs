@@ -9,7 +9,8 @@ use std::str::FromStr;
 use std::ops::Range;
 use num::abs;
 use regex::Regex;
-use num::integer::lcm;
+use aoc::cycle::{find_periodic, Cycle, Periodic};
+use aoc::number_theory::crt;
 
 #[derive(Clone, Debug)]
 struct Node {
@@ -67,28 +68,54 @@ fn part_1((instructions, network): &(String, HashMap<String, Node>)) -> i64 {
     steps
 }
 
+// Each ghost's walk ends in a loop over (node, instruction index) states, so the steps at which
+// it sits on a Z node are a few early hits plus residues modulo the loop length.
 fn part_2((instructions, network): &(String, HashMap<String, Node>)) -> i64 {
-    let starts = network.keys()
+    let instructions = instructions.chars().collect_vec();
+    let walks = network.keys()
         .filter(|id| id.ends_with("A"))
-        .collect_vec();
-    starts.iter()
         .map(|start| {
-            let mut current = start.clone();
-            let mut steps: i64 = 0;
-            let mut instructions = instructions.chars().cycle();
-
-            while !current.ends_with("Z") {
-                steps += 1;
-                match instructions.next().unwrap() {
-                    'L' => current = &network[current].left,
-                    'R' => current = &network[current].right,
-                    _ => panic!("Unexpected instruction!"),
-                }
+            find_periodic(
+                (start.as_str(), 0),
+                |&(current, i)| {
+                    let next = match instructions[i] {
+                        'L' => &network[current].left,
+                        'R' => &network[current].right,
+                        _ => panic!("Unexpected instruction!"),
+                    };
+                    (next.as_str(), (i + 1) % instructions.len())
+                },
+                |state| *state,
+            )
+        })
+        .collect_vec();
+    let at_end = |walk: &Periodic<(&str, usize)>, step: usize| walk.state_at(step).0.ends_with("Z");
+
+    let settled = walks.iter().map(|walk| walk.cycle.start).max().unwrap();
+    if let Some(step) = (0..settled).find(|step| walks.iter().all(|walk| at_end(walk, *step))) {
+        return step as i64;
+    }
+
+    walks.iter()
+        .map(|walk| {
+            let Cycle { start, length } = walk.cycle;
+            (start..start + length)
+                .filter(|step| at_end(walk, *step))
+                .map(|step| (step as i64, length as i64))
+                .collect_vec()
+        })
+        .multi_cartesian_product()
+        .filter_map(|congruences| crt(&congruences))
+        .map(|(step, period)| {
+            let settled = settled as i64;
+            if step < settled {
+                step + (settled - step + period - 1) / period * period
+            } else {
+                step
             }
-            steps
         })
-        .reduce(|acc, step| lcm(acc, step))
-        .unwrap()
+        .min()
+        .expect("The ghosts never all reach Z together")
 }
 
 fn main() {
@@ -11,6 +11,7 @@ use std::ops::Range;
 use num::abs;
 use regex::Regex;
 use num::integer::lcm;
+use aoc::cycle::state_at;
 
 enum Direction {
     North,
@@ -150,20 +151,8 @@ fn part_1(map: &Map) -> i64 {
 }
 
 fn part_2(map: &Map) -> i64 {
-    let mut previous = BTreeMap::new();
-    let mut next_map = map.clone();
-    let iterations = 1_000_000_000;
-    for i in 0..iterations {
-        if previous.contains_key(&next_map.round) {
-            let cycle_length = i - previous[&next_map.round];
-            if (iterations - i) % cycle_length == 0 {
-                break;
-            }
-        }
-        previous.insert(next_map.round.clone(), i);
-        next_map = next_map.cycle();
-    }
-    next_map.load()
+    state_at(map.clone(), |m| m.cycle(), |m| m.round.clone(), 1_000_000_000)
+        .load()
 }
 
 fn main() {
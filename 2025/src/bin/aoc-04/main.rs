@@ -1,3 +1,4 @@
+use aoc::automaton::{Automaton, Sparse};
 use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::env;
@@ -38,36 +39,20 @@ fn adjacent_positions(pos: &(i64, i64)) -> HashSet<(i64, i64)> {
         .collect()
 }
 
-fn remove_rolls(rolls: &HashSet<(i64, i64)>) -> HashSet<(i64, i64)> {
-    rolls
-        .iter()
-        .filter(|roll| {
-            adjacent_positions(roll)
-                .iter()
-                .filter(|pos| rolls.contains(pos))
-                .count()
-                >= 4
-        })
-        .cloned()
-        .collect()
+fn stays(roll: bool, neighbours: usize) -> bool {
+    roll && neighbours >= 4
 }
 
 fn part_1(rolls: &HashSet<(i64, i64)>) -> usize {
-    let next_rolls = remove_rolls(rolls);
-    rolls.len() - next_rolls.len()
+    let mut removal = Sparse::new(rolls.iter().cloned(), adjacent_positions, stays);
+    removal.step();
+    rolls.len() - removal.live().len()
 }
 
 fn part_2(rolls: &HashSet<(i64, i64)>) -> usize {
-    let mut current_rolls = rolls.clone();
-    loop {
-        let next_rolls = remove_rolls(&current_rolls);
-        if next_rolls.len() == current_rolls.len() {
-            break;
-        }
-        current_rolls = next_rolls;
-    }
-
-    rolls.len() - current_rolls.len()
+    let mut removal = Sparse::new(rolls.iter().cloned(), adjacent_positions, stays);
+    removal.run_to_fixed_point();
+    rolls.len() - removal.live().len()
 }
 
 fn main() {
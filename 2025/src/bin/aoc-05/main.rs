@@ -1,13 +1,11 @@
-use std::cmp::max;
-use std::cmp::min;
+use std::convert::TryInto;
 use std::env;
 use std::fs;
-use std::ops::RangeInclusive;
 use std::time::SystemTime;
 
-use itertools::Itertools;
+use aoc::interval::IntervalSet;
 
-fn parse_input(input: &str) -> (Vec<RangeInclusive<i64>>, Vec<i64>) {
+fn parse_input(input: &str) -> (IntervalSet<i64>, Vec<i64>) {
     let (ranges, ingredients) = input.trim().split_once("\n\n").unwrap();
 
     let ranges = ranges
@@ -28,41 +26,15 @@ fn parse_input(input: &str) -> (Vec<RangeInclusive<i64>>, Vec<i64>) {
     (ranges, ingredients)
 }
 
-fn part_1((ranges, ingredients): &(Vec<RangeInclusive<i64>>, Vec<i64>)) -> usize {
+fn part_1((ranges, ingredients): &(IntervalSet<i64>, Vec<i64>)) -> usize {
     ingredients
         .iter()
-        .filter(|ingredient| ranges.iter().any(|range| range.contains(ingredient)))
+        .filter(|ingredient| ranges.contains(**ingredient))
         .count()
 }
 
-fn part_2((ranges, _ingredients): &(Vec<RangeInclusive<i64>>, Vec<i64>)) -> usize {
-    let mut combined_ranges = vec![];
-    ranges
-        .iter()
-        .sorted_by_key(|range| range.start())
-        .for_each(|range| {
-            if combined_ranges.last().is_none() {
-                combined_ranges.push(range.clone());
-            } else {
-                let curr = combined_ranges.last().unwrap().clone();
-                if curr.contains(range.start())
-                    || curr.contains(range.end())
-                    || range.contains(curr.start())
-                    || range.contains(curr.end())
-                {
-                    combined_ranges.pop();
-                    combined_ranges
-                        .push(*min(curr.start(), range.start())..=*max(curr.end(), range.end()));
-                } else {
-                    combined_ranges.push(range.clone());
-                }
-            }
-        });
-
-    combined_ranges
-        .iter()
-        .map(|range| range.size_hint().0)
-        .sum()
+fn part_2((ranges, _ingredients): &(IntervalSet<i64>, Vec<i64>)) -> usize {
+    ranges.len().try_into().unwrap()
 }
 
 fn main() {
@@ -1,6 +1,4 @@
 use std::collections::BTreeMap;
-use std::collections::HashMap;
-use std::collections::HashSet;
 use std::collections::VecDeque;
 use std::convert::TryFrom;
 use std::env;
@@ -8,100 +6,36 @@ use std::fs;
 use std::str::FromStr;
 use std::time::SystemTime;
 
+use aoc::point::Point3;
+use aoc::union_find::{kruskal, UnionFind};
 use itertools::Itertools;
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct Pos {
-    x: i64,
-    y: i64,
-    z: i64,
+fn parse_input(input: &str) -> Vec<Point3> {
+    input.lines().map(Point3::from_str).try_collect().unwrap()
 }
 
-impl Pos {
-    fn distance(&self, other: &Pos) -> f64 {
-        (((self.x - other.x) * (self.x - other.x)
-            + (self.y - other.y) * (self.y - other.y)
-            + (self.z - other.z) * (self.z - other.z)) as f64)
-            .sqrt()
-    }
+// Every pair of boxes, weighted by their squared distance apart
+fn connections(boxes: &[Point3]) -> Vec<(usize, usize, i64)> {
+    (0..boxes.len())
+        .tuple_combinations()
+        .map(|(a, b)| (a, b, boxes[a].euclidean_squared(&boxes[b])))
+        .sorted_by_key(|c| c.2)
+        .collect()
 }
 
-impl FromStr for Pos {
-    type Err = std::string::ParseError;
-
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let (x, y, z) = input
-            .splitn(3, ",")
-            .map(|s| s.parse().unwrap())
-            .collect_tuple()
-            .unwrap();
-        Ok(Pos { x, y, z })
+fn part_1(boxes: &Vec<Point3>, connections_made: usize) -> usize {
+    let mut circuits = UnionFind::new(boxes.len());
+    for (a, b, _) in connections(boxes).into_iter().take(connections_made) {
+        circuits.union(a, b);
     }
-}
-
-fn parse_input(input: &str) -> Vec<Pos> {
-    input.lines().map(Pos::from_str).try_collect().unwrap()
-}
-
-fn part_1(boxes: &Vec<Pos>, connections: usize) -> usize {
-    let mut circuit_map: HashMap<&Pos, Option<i64>> = boxes.iter().map(|p| (p, None)).collect();
 
-    let mut circuit_counter = 0;
-
-    boxes
-        .iter()
-        .combinations(2)
-        .sorted_by(|a, b| a[0].distance(a[1]).total_cmp(&b[0].distance(b[1])))
-        .take(connections)
-        .for_each(|connection| {
-            if circuit_map[connection[0]].is_some() && circuit_map[connection[1]].is_none() {
-                *circuit_map.get_mut(connection[1]).unwrap() = circuit_map[connection[0]];
-            } else if circuit_map[connection[0]].is_none() && circuit_map[connection[1]].is_some() {
-                *circuit_map.get_mut(connection[0]).unwrap() = circuit_map[connection[1]];
-            } else if circuit_map[connection[0]].is_none() && circuit_map[connection[1]].is_none() {
-                *circuit_map.get_mut(connection[0]).unwrap() = Some(circuit_counter);
-                *circuit_map.get_mut(connection[1]).unwrap() = Some(circuit_counter);
-                circuit_counter += 1;
-            } else {
-                let old_circuit_number = circuit_map[connection[0]];
-                let new_circuit_number = circuit_map[connection[1]];
-                circuit_map
-                    .iter_mut()
-                    .filter(|(_, p)| **p == old_circuit_number)
-                    .for_each(|(_, p)| *p = new_circuit_number);
-            }
-        });
-
-    circuit_map
-        .values()
-        .filter(|n| n.is_some())
-        .counts()
-        .values()
-        .sorted()
-        .rev()
-        .take(3)
-        .product()
+    circuits.component_sizes().iter().take(3).product()
 }
 
-fn part_2(boxes: &Vec<Pos>) -> i64 {
-    let mut handled = HashSet::<&Pos>::new();
-
-    let ordered_boxes = boxes
-        .iter()
-        .combinations(2)
-        .sorted_by(|a, b| a[0].distance(a[1]).total_cmp(&b[0].distance(b[1])));
-
-    for b in ordered_boxes {
-        if !handled.contains(b[0]) || !handled.contains(b[1]) {
-            handled.insert(b[0]);
-            handled.insert(b[1]);
-
-            if handled.len() == boxes.len() {
-                return b[0].x * b[1].x;
-            }
-        }
-    }
-    panic!("Never reached a solution!");
+fn part_2(boxes: &Vec<Point3>) -> i64 {
+    let circuits = kruskal(boxes.len(), connections(boxes));
+    let (a, b, _) = circuits.joining_edge().expect("Never reached a solution!");
+    boxes[*a].x() * boxes[*b].x()
 }
 
 fn main() {
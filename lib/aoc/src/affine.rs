@@ -0,0 +1,181 @@
+use crate::number_theory::{addmod, modinverse, mulmod};
+use std::str::FromStr;
+
+// The map x -> a*x + b modulo m, such as where a card moves to in a shuffled deck of m cards
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AffineMod {
+    pub a: i64,
+    pub b: i64,
+    pub m: i64,
+}
+
+impl AffineMod {
+    pub fn new(a: i64, b: i64, m: i64) -> AffineMod {
+        AffineMod {
+            a: a.rem_euclid(m),
+            b: b.rem_euclid(m),
+            m,
+        }
+    }
+
+    pub fn identity(m: i64) -> AffineMod {
+        AffineMod::new(1, 0, m)
+    }
+
+    pub fn apply(&self, x: i64) -> i64 {
+        addmod(mulmod(self.a, x, self.m), self.b, self.m)
+    }
+
+    // Applies self first and then other
+    pub fn then(&self, other: &AffineMod) -> AffineMod {
+        assert_eq!(self.m, other.m, "Cannot compose maps with different moduli");
+        AffineMod::new(
+            mulmod(other.a, self.a, self.m),
+            addmod(mulmod(other.a, self.b, self.m), other.b, self.m),
+            self.m,
+        )
+    }
+
+    // The map applied n times in a row, by repeated squaring
+    pub fn pow(&self, mut n: u64) -> AffineMod {
+        let mut result = AffineMod::identity(self.m);
+        let mut square = *self;
+        while n > 0 {
+            if n & 1 == 1 {
+                result = result.then(&square);
+            }
+            square = square.then(&square);
+            n >>= 1;
+        }
+        result
+    }
+
+    // x = a^-1 * (y - b), if a is invertible
+    pub fn inverse(&self) -> Option<AffineMod> {
+        let inverse = modinverse(self.a, self.m)?;
+        Some(AffineMod::new(
+            inverse,
+            -mulmod(inverse, self.b, self.m),
+            self.m,
+        ))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Technique {
+    DealIntoNewStack,
+    Cut(i64),
+    DealWithIncrement(i64),
+}
+
+impl Technique {
+    // Where each card's position moves to in a deck of `size` cards
+    pub fn as_affine(&self, size: i64) -> AffineMod {
+        match self {
+            Technique::DealIntoNewStack => AffineMod::new(-1, -1, size),
+            Technique::Cut(n) => AffineMod::new(1, -n, size),
+            Technique::DealWithIncrement(n) => AffineMod::new(*n, 0, size),
+        }
+    }
+}
+
+impl FromStr for Technique {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        let number = |s: &str| {
+            s.trim()
+                .parse::<i64>()
+                .map_err(|e| format!("Bad number in {:?}: {}", input, e))
+        };
+        if input == "deal into new stack" {
+            Ok(Technique::DealIntoNewStack)
+        } else if let Some(n) = input.strip_prefix("cut ") {
+            Ok(Technique::Cut(number(n)?))
+        } else if let Some(n) = input.strip_prefix("deal with increment ") {
+            Ok(Technique::DealWithIncrement(number(n)?))
+        } else {
+            Err(format!("Unknown technique: {:?}", input))
+        }
+    }
+}
+
+pub fn parse_shuffle(input: &str) -> Result<Vec<Technique>, String> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.parse())
+        .collect()
+}
+
+// The whole shuffle as a single map from starting position to final position
+pub fn shuffle_map(techniques: &[Technique], size: i64) -> AffineMod {
+    techniques
+        .iter()
+        .fold(AffineMod::identity(size), |map, technique| {
+            map.then(&technique.as_affine(size))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deck(techniques: &str) -> Vec<i64> {
+        let map = shuffle_map(&parse_shuffle(techniques).unwrap(), 10);
+        let mut deck = vec![0; 10];
+        (0..10).for_each(|card| deck[map.apply(card) as usize] = card);
+        deck
+    }
+
+    #[test]
+    fn shuffles() {
+        assert_eq!(
+            deck("deal with increment 7\ndeal into new stack\ndeal into new stack"),
+            vec![0, 3, 6, 9, 2, 5, 8, 1, 4, 7]
+        );
+        assert_eq!(
+            deck("cut 6\ndeal with increment 7\ndeal into new stack"),
+            vec![3, 0, 7, 4, 1, 8, 5, 2, 9, 6]
+        );
+        assert_eq!(
+            deck(
+                "deal into new stack\ncut -2\ndeal with increment 7\ncut 8\ncut -4\n\
+                 deal with increment 7\ncut 3\ndeal with increment 9\ndeal with increment 3\ncut -1"
+            ),
+            vec![9, 2, 5, 8, 1, 4, 7, 0, 3, 6]
+        );
+        assert!(parse_shuffle("cut sideways").is_err());
+        assert!(parse_shuffle("shuffle thoroughly").is_err());
+    }
+
+    #[test]
+    fn algebra() {
+        let f = AffineMod::new(3, 5, 11);
+        let g = AffineMod::new(-2, 7, 11);
+        assert_eq!(f.then(&g).apply(4), g.apply(f.apply(4)));
+        assert_eq!(f.pow(0), AffineMod::identity(11));
+        assert_eq!(f.pow(5).apply(2), (0..5).fold(2, |x, _| f.apply(x)));
+        assert_eq!(f.then(&f.inverse().unwrap()), AffineMod::identity(11));
+        assert_eq!(AffineMod::new(2, 1, 4).inverse(), None);
+
+        let big = AffineMod::new(119315717514046, 3, 119315717514047);
+        let repeated = big.pow(101741582076661);
+        assert_eq!(
+            repeated.inverse().unwrap().apply(repeated.apply(2020)),
+            2020
+        );
+
+        // Sums of two values below a modulus this size don't fit in an i64
+        let m = 9223372036854775783;
+        let huge = AffineMod::new(m - 2, m - 3, m);
+        assert_eq!(huge.apply(m - 1), m - 1);
+        assert_eq!(huge.then(&huge).apply(m - 1), m - 1);
+        let repeated = huge.pow(1 << 40);
+        assert_eq!(
+            repeated.inverse().unwrap().apply(repeated.apply(m - 7)),
+            m - 7
+        );
+    }
+}
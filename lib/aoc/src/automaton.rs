@@ -0,0 +1,226 @@
+use crate::grid::Grid2D;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::mem;
+
+pub trait Automaton {
+    // Advances one generation, returning whether any cell changed
+    fn step(&mut self) -> bool;
+
+    fn generation(&self) -> usize;
+
+    fn run(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+
+    // Steps until a generation changes nothing, returning how many steps that took including the
+    // final unchanged one
+    fn run_to_fixed_point(&mut self) -> usize {
+        let start = self.generation();
+        while self.step() {}
+        self.generation() - start
+    }
+}
+
+// Every cell of a fixed grid updated at once. Each cell's neighbourhood is worked out once from the
+// starting grid, so it may depend on parts of the layout that never change, such as floor.
+pub struct Dense<T, R> {
+    grid: Grid2D<T>,
+    buffer: Grid2D<T>,
+    neighbours: Vec<Vec<(usize, usize)>>,
+    rule: R,
+    generation: usize,
+}
+
+impl<T, R> Dense<T, R>
+where
+    T: Clone + PartialEq,
+    R: FnMut(&T, &[&T]) -> T,
+{
+    pub fn new(
+        grid: Grid2D<T>,
+        neighbourhood: impl Fn(&Grid2D<T>, (usize, usize)) -> Vec<(usize, usize)>,
+        rule: R,
+    ) -> Dense<T, R> {
+        let neighbours = grid.positions().map(|p| neighbourhood(&grid, p)).collect();
+        Dense {
+            buffer: grid.clone(),
+            grid,
+            neighbours,
+            rule,
+            generation: 0,
+        }
+    }
+
+    pub fn grid(&self) -> &Grid2D<T> {
+        &self.grid
+    }
+
+    pub fn into_grid(self) -> Grid2D<T> {
+        self.grid
+    }
+}
+
+impl<T, R> Automaton for Dense<T, R>
+where
+    T: Clone + PartialEq,
+    R: FnMut(&T, &[&T]) -> T,
+{
+    fn step(&mut self) -> bool {
+        let Dense {
+            grid,
+            buffer,
+            neighbours,
+            rule,
+            ..
+        } = self;
+        let mut changed = false;
+        let mut states = Vec::new();
+        for (pos, neighbours) in grid.positions().zip(neighbours.iter()) {
+            states.clear();
+            states.extend(neighbours.iter().map(|n| &grid[*n]));
+            let next = rule(&grid[pos], &states);
+            changed |= next != grid[pos];
+            buffer[pos] = next;
+        }
+        mem::swap(grid, buffer);
+        self.generation += 1;
+        changed
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+}
+
+// Two-state cells on an unbounded space, storing only the live ones. The rule is given whether a
+// cell is alive and how many of its neighbours are.
+pub struct Sparse<P, N, R> {
+    live: HashSet<P>,
+    buffer: HashSet<P>,
+    counts: HashMap<P, usize>,
+    neighbourhood: N,
+    rule: R,
+    generation: usize,
+}
+
+impl<P, I, N, R> Sparse<P, N, R>
+where
+    P: Clone + Eq + Hash,
+    I: IntoIterator<Item = P>,
+    N: Fn(&P) -> I,
+    R: FnMut(bool, usize) -> bool,
+{
+    pub fn new(live: impl IntoIterator<Item = P>, neighbourhood: N, rule: R) -> Sparse<P, N, R> {
+        Sparse {
+            live: live.into_iter().collect(),
+            buffer: HashSet::new(),
+            counts: HashMap::new(),
+            neighbourhood,
+            rule,
+            generation: 0,
+        }
+    }
+
+    pub fn live(&self) -> &HashSet<P> {
+        &self.live
+    }
+
+    pub fn into_live(self) -> HashSet<P> {
+        self.live
+    }
+}
+
+impl<P, I, N, R> Automaton for Sparse<P, N, R>
+where
+    P: Clone + Eq + Hash,
+    I: IntoIterator<Item = P>,
+    N: Fn(&P) -> I,
+    R: FnMut(bool, usize) -> bool,
+{
+    fn step(&mut self) -> bool {
+        self.counts.clear();
+        for cell in &self.live {
+            for neighbour in (self.neighbourhood)(cell) {
+                *self.counts.entry(neighbour).or_insert(0) += 1;
+            }
+        }
+
+        self.buffer.clear();
+        for (cell, count) in &self.counts {
+            if (self.rule)(self.live.contains(cell), *count) {
+                self.buffer.insert(cell.clone());
+            }
+        }
+        for cell in &self.live {
+            if !self.counts.contains_key(cell) && (self.rule)(true, 0) {
+                self.buffer.insert(cell.clone());
+            }
+        }
+
+        let changed = self.buffer != self.live;
+        mem::swap(&mut self.live, &mut self.buffer);
+        self.generation += 1;
+        changed
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+}
+
+// Conway's rules, as used by most puzzles built on the Game of Life
+pub fn life(alive: bool, neighbours: usize) -> bool {
+    neighbours == 3 || (alive && neighbours == 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point::Point2;
+
+    #[test]
+    fn dense_blinker() {
+        let grid: Grid2D<char> = ".....\n..#..\n..#..\n..#..\n.....".parse().unwrap();
+        let mut automaton = Dense::new(
+            grid.clone(),
+            |g, p| g.neighbours_8(p).collect(),
+            |cell, neighbours| {
+                let alive = neighbours.iter().filter(|n| ***n == '#').count();
+                if life(*cell == '#', alive) {
+                    '#'
+                } else {
+                    '.'
+                }
+            },
+        );
+        assert!(automaton.step());
+        assert_eq!(
+            automaton.grid().render(|c| *c),
+            ".....\n.....\n.###.\n.....\n....."
+        );
+        automaton.run(3);
+        assert_eq!((automaton.generation(), automaton.grid()), (4, &grid));
+    }
+
+    #[test]
+    fn sparse_life() {
+        let cells = |points: &[(i64, i64)]| points.iter().map(|p| Point2::from(*p)).collect();
+        let glider: HashSet<Point2> = cells(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        let mut automaton = Sparse::new(glider.clone(), |p: &Point2| p.neighbours(), life);
+        automaton.run(4);
+        let moved: HashSet<Point2> = glider.iter().map(|p| *p + Point2::new(1, 1)).collect();
+        assert_eq!(automaton.live(), &moved);
+
+        // Three corners of a square fill in the fourth and then stay put
+        let mut automaton = Sparse::new(
+            cells(&[(0, 0), (1, 0), (0, 1)]) as HashSet<Point2>,
+            |p: &Point2| p.neighbours(),
+            life,
+        );
+        assert_eq!(automaton.run_to_fixed_point(), 2);
+        assert_eq!(automaton.into_live().len(), 4);
+    }
+}
@@ -0,0 +1,321 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+// An odd base whose digits run symmetrically from -(base - 1) / 2 to (base - 1) / 2, written
+// with one character per digit listed from most negative to most positive
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Radix {
+    alphabet: &'static str,
+}
+
+impl Radix {
+    pub const SNAFU: Radix = Radix { alphabet: "=-012" };
+    pub const TERNARY: Radix = Radix { alphabet: "T01" };
+
+    pub fn new(alphabet: &'static str) -> Result<Radix, String> {
+        let chars: Vec<char> = alphabet.chars().collect();
+        if chars.len() < 3 || chars.len().is_multiple_of(2) {
+            return Err(format!(
+                "A balanced alphabet needs an odd number of digits, got {:?}",
+                alphabet
+            ));
+        }
+        for (i, c) in chars.iter().enumerate() {
+            if chars[..i].contains(c) {
+                return Err(format!("Digit {:?} repeated in {:?}", c, alphabet));
+            }
+        }
+        Ok(Radix { alphabet })
+    }
+
+    pub fn base(&self) -> i64 {
+        self.alphabet.chars().count() as i64
+    }
+
+    // The largest digit, or equally the magnitude of the smallest
+    fn half(&self) -> i64 {
+        (self.base() - 1) / 2
+    }
+
+    fn digit(&self, c: char) -> Option<i64> {
+        self.alphabet
+            .chars()
+            .position(|d| d == c)
+            .map(|i| i as i64 - self.half())
+    }
+
+    fn symbol(&self, digit: i64) -> char {
+        self.alphabet
+            .chars()
+            .nth((digit + self.half()) as usize)
+            .unwrap()
+    }
+
+    pub fn parse(&self, s: &str) -> Result<Balanced, String> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("Empty number".to_string());
+        }
+        let digits = s
+            .chars()
+            .rev()
+            .map(|c| {
+                self.digit(c)
+                    .ok_or_else(|| format!("Unexpected digit {:?} in {:?}", c, s))
+            })
+            .collect::<Result<Vec<i64>, String>>()?;
+        Ok(Balanced::normalized(*self, digits))
+    }
+
+    pub fn zero(&self) -> Balanced {
+        Balanced {
+            radix: *self,
+            digits: Vec::new(),
+        }
+    }
+
+    pub fn from_int(&self, n: i128) -> Balanced {
+        let base = self.base() as i128;
+        let half = self.half() as i128;
+        let mut digits = Vec::new();
+        let mut rest = n;
+        while rest != 0 {
+            let mut digit = rest.rem_euclid(base);
+            let mut quotient = rest.div_euclid(base);
+            if digit > half {
+                digit -= base;
+                quotient += 1;
+            }
+            digits.push(digit as i64);
+            rest = quotient;
+        }
+        Balanced {
+            radix: *self,
+            digits,
+        }
+    }
+}
+
+// An arbitrarily long number in a balanced radix, with arithmetic done on the digits directly
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Balanced {
+    radix: Radix,
+    // Least significant first, with no trailing zeros so that zero has no digits at all
+    digits: Vec<i64>,
+}
+
+impl Balanced {
+    // Carries anything outside the digit range into the next place up
+    fn normalized(radix: Radix, mut digits: Vec<i64>) -> Balanced {
+        let base = radix.base();
+        let half = radix.half();
+        let mut carry = 0;
+        let mut i = 0;
+        while i < digits.len() || carry != 0 {
+            if i == digits.len() {
+                digits.push(0);
+            }
+            let value = digits[i] + carry;
+            carry = (value + half).div_euclid(base);
+            digits[i] = value - carry * base;
+            i += 1;
+        }
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        Balanced { radix, digits }
+    }
+
+    pub fn radix(&self) -> Radix {
+        self.radix
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    // The sign is that of the leading digit, as the rest can never outweigh it
+    pub fn signum(&self) -> i64 {
+        self.digits.last().map_or(0, |d| d.signum())
+    }
+
+    pub fn to_int(&self) -> Option<i128> {
+        let base = self.radix.base() as i128;
+        self.digits.iter().rev().try_fold(0i128, |total, d| {
+            total.checked_mul(base)?.checked_add(*d as i128)
+        })
+    }
+
+    fn same_radix(&self, other: &Balanced) {
+        assert_eq!(
+            self.radix, other.radix,
+            "Can't combine numbers in different radices"
+        );
+    }
+}
+
+impl fmt::Display for Balanced {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.digits.is_empty() {
+            return write!(f, "{}", self.radix.symbol(0));
+        }
+        for d in self.digits.iter().rev() {
+            write!(f, "{}", self.radix.symbol(*d))?;
+        }
+        Ok(())
+    }
+}
+
+impl Add for Balanced {
+    type Output = Balanced;
+
+    fn add(self, other: Balanced) -> Balanced {
+        self.same_radix(&other);
+        let (mut digits, shorter) = if self.digits.len() >= other.digits.len() {
+            (self.digits, other.digits)
+        } else {
+            (other.digits, self.digits)
+        };
+        for (d, s) in digits.iter_mut().zip(shorter) {
+            *d += s;
+        }
+        Balanced::normalized(self.radix, digits)
+    }
+}
+
+impl Neg for Balanced {
+    type Output = Balanced;
+
+    // Every digit is symmetric about zero, so negating is digit by digit
+    fn neg(self) -> Balanced {
+        Balanced {
+            radix: self.radix,
+            digits: self.digits.into_iter().map(|d| -d).collect(),
+        }
+    }
+}
+
+impl Sub for Balanced {
+    type Output = Balanced;
+
+    fn sub(self, other: Balanced) -> Balanced {
+        self + -other
+    }
+}
+
+impl Mul for Balanced {
+    type Output = Balanced;
+
+    fn mul(self, other: Balanced) -> Balanced {
+        self.same_radix(&other);
+        if self.is_zero() || other.is_zero() {
+            return self.radix.zero();
+        }
+        let mut digits = vec![0; self.digits.len() + other.digits.len()];
+        for (i, a) in self.digits.iter().enumerate() {
+            for (j, b) in other.digits.iter().enumerate() {
+                digits[i + j] += a * b;
+            }
+        }
+        Balanced::normalized(self.radix, digits)
+    }
+}
+
+impl PartialOrd for Balanced {
+    fn partial_cmp(&self, other: &Balanced) -> Option<Ordering> {
+        if self.radix != other.radix {
+            return None;
+        }
+        Some((self.clone() - other.clone()).signum().cmp(&0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Lcg;
+
+    fn samples(count: usize) -> Vec<i64> {
+        let mut random = Lcg::new(7);
+        (0..count)
+            .map(|i| {
+                let seed = random.next_u64();
+                // Mix in plenty of small values, where the carries are most interesting
+                match i % 3 {
+                    0 => (seed >> 32) as i64 % 100,
+                    1 => (seed >> 16) as i64 % 1_000_000_000,
+                    _ => seed as i64,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn snafu() {
+        let examples = [
+            (1, "1"),
+            (2, "2"),
+            (3, "1="),
+            (4, "1-"),
+            (5, "10"),
+            (8, "2="),
+            (2022, "1=11-2"),
+            (12345, "1-0---0"),
+            (314159265, "1121-1110-1=0"),
+        ];
+        for (n, s) in examples.iter() {
+            assert_eq!(Radix::SNAFU.from_int(*n).to_string(), *s);
+            assert_eq!(Radix::SNAFU.parse(s).unwrap().to_int(), Some(*n));
+        }
+        assert_eq!(Radix::SNAFU.zero().to_string(), "0");
+        assert_eq!(Radix::SNAFU.parse("00-1").unwrap().to_string(), "-1");
+        assert_eq!(Radix::TERNARY.from_int(-5).to_string(), "T11");
+        assert!(Radix::SNAFU.parse("1x").is_err());
+        assert!(Radix::new("abcd").is_err());
+        assert!(Radix::new("aba").is_err());
+    }
+
+    #[test]
+    fn round_trips() {
+        let radices = [
+            Radix::SNAFU,
+            Radix::TERNARY,
+            Radix::new("zyxwv0ABCDE").unwrap(),
+        ];
+        for radix in radices.iter() {
+            for n in samples(300) {
+                let n = n as i128;
+                let number = radix.from_int(n);
+                assert_eq!(number.to_int(), Some(n));
+                assert_eq!(radix.parse(&number.to_string()), Ok(number.clone()));
+                assert_eq!(number.signum() as i128, n.signum());
+                assert_eq!((-number).to_int(), Some(-n));
+            }
+        }
+    }
+
+    #[test]
+    fn arithmetic() {
+        let values = samples(200);
+        for radix in [Radix::SNAFU, Radix::TERNARY].iter() {
+            for pair in values.windows(2) {
+                let (a, b) = (pair[0] as i128, pair[1] as i128);
+                let (x, y) = (radix.from_int(a), radix.from_int(b));
+                assert_eq!((x.clone() + y.clone()).to_int(), Some(a + b));
+                assert_eq!((x.clone() - y.clone()).to_int(), Some(a - b));
+                assert_eq!((x.clone() * y.clone()).to_int(), Some(a * b));
+                assert_eq!(x.partial_cmp(&y), Some(a.cmp(&b)));
+            }
+        }
+    }
+
+    #[test]
+    fn beyond_i128() {
+        let big = Radix::SNAFU.from_int(i128::MAX);
+        let square = big.clone() * big.clone();
+        assert_eq!(square.to_int(), None);
+        assert_eq!((square.clone() - big.clone() * big).to_int(), Some(0));
+        assert!((square.clone() + -square).is_zero());
+    }
+}
@@ -0,0 +1,277 @@
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+const HISTORY_HEADER: &str = "timestamp,year,day,phase,runs,median_ns,min_ns,max_ns";
+
+// Parses the `{:?}` output of a `Duration`, e.g. `1.5s`, `12.3µs` or `300ns`
+pub fn parse_duration(input: &str) -> Option<Duration> {
+    let input = input.trim();
+    let (number, scale) = if let Some(n) = input.strip_suffix("ns") {
+        (n, 1e-9)
+    } else if let Some(n) = input.strip_suffix("µs") {
+        (n, 1e-6)
+    } else if let Some(n) = input.strip_suffix("us") {
+        (n, 1e-6)
+    } else if let Some(n) = input.strip_suffix("ms") {
+        (n, 1e-3)
+    } else if let Some(n) = input.strip_suffix('s') {
+        (n, 1.0)
+    } else {
+        return None;
+    };
+    let value = f64::from_str(number).ok()?;
+    if value < 0.0 || !value.is_finite() {
+        return None;
+    }
+    Some(Duration::from_secs_f64(value * scale))
+}
+
+// Picks out the `Setup: ...`/`Part 1: ...` lines printed after the time breakdown header,
+// ignoring the answers which are printed in the same format before it.
+pub fn parse_timings(output: &str) -> Vec<(String, Duration)> {
+    output
+        .lines()
+        .skip_while(|line| !line.starts_with("Time b"))
+        .skip(1)
+        .filter_map(|line| {
+            let (phase, value) = line.split_once(':')?;
+            Some((phase.trim().to_string(), parse_duration(value)?))
+        })
+        .collect()
+}
+
+// Whether a day's binary, e.g. `aoc-02`, is the one asked for by name or by number, so that
+// `2` picks out day 2 but not days 12 and 22
+pub fn matches_day(day: &str, filter: &str) -> bool {
+    let number = |s: &str| u32::from_str(s.rsplit('-').next()?).ok();
+    day == filter || (number(filter).is_some() && number(filter) == number(day))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Summary {
+    pub median: Duration,
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl Summary {
+    pub fn from_samples(samples: &[Duration]) -> Option<Summary> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let mid = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[mid - 1] + sorted[mid]) / 2
+        } else {
+            sorted[mid]
+        };
+        Some(Summary {
+            median,
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+        })
+    }
+
+    pub fn spread(&self) -> Duration {
+        self.max - self.min
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub timestamp: u64,
+    pub year: String,
+    pub day: String,
+    pub phase: String,
+    pub runs: usize,
+    pub summary: Summary,
+}
+
+impl Record {
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{}",
+            self.timestamp,
+            self.year,
+            self.day,
+            self.phase,
+            self.runs,
+            self.summary.median.as_nanos(),
+            self.summary.min.as_nanos(),
+            self.summary.max.as_nanos()
+        )
+    }
+}
+
+impl FromStr for Record {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = input.trim().split(',').collect();
+        if fields.len() != 8 {
+            return Err(format!(
+                "Expected 8 fields, found {}: {}",
+                fields.len(),
+                input
+            ));
+        }
+        let number = |i: usize| {
+            u64::from_str(fields[i]).map_err(|e| format!("Bad field {} in {}: {}", i, input, e))
+        };
+        Ok(Record {
+            timestamp: number(0)?,
+            year: fields[1].to_string(),
+            day: fields[2].to_string(),
+            phase: fields[3].to_string(),
+            runs: number(4)? as usize,
+            summary: Summary {
+                median: Duration::from_nanos(number(5)?),
+                min: Duration::from_nanos(number(6)?),
+                max: Duration::from_nanos(number(7)?),
+            },
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct History {
+    pub records: Vec<Record>,
+}
+
+impl History {
+    pub fn load(path: &Path) -> io::Result<History> {
+        if !path.exists() {
+            return Ok(History::default());
+        }
+        let records = fs::read_to_string(path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty() && *line != HISTORY_HEADER)
+            .map(|line| {
+                Record::from_str(line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(History { records })
+    }
+
+    pub fn last(&self, year: &str, day: &str, phase: &str) -> Option<&Record> {
+        self.records
+            .iter()
+            .rev()
+            .find(|r| r.year == year && r.day == day && r.phase == phase)
+    }
+
+    pub fn append(path: &Path, records: &[Record]) -> io::Result<()> {
+        let new_file = !path.exists();
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        if new_file {
+            writeln!(file, "{}", HISTORY_HEADER)?;
+        }
+        for record in records {
+            writeln!(file, "{}", record.to_csv())?;
+        }
+        Ok(())
+    }
+}
+
+// Percentage change of the median, positive when the current run is slower
+pub fn change(previous: &Summary, current: &Summary) -> f64 {
+    let previous = previous.median.as_secs_f64();
+    if previous == 0.0 {
+        return 0.0;
+    }
+    (current.median.as_secs_f64() - previous) / previous * 100.0
+}
+
+pub fn is_regression(previous: &Summary, current: &Summary, threshold: f64) -> bool {
+    change(previous, current) > threshold
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("1.5s"), Some(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("12.5ms"), Some(Duration::from_micros(12500)));
+        assert_eq!(parse_duration("30µs"), Some(Duration::from_micros(30)));
+        assert_eq!(parse_duration("300ns"), Some(Duration::from_nanos(300)));
+        assert_eq!(parse_duration("300"), None);
+        assert_eq!(parse_duration("fast"), None);
+    }
+
+    #[test]
+    fn timings() {
+        let output = "Part 1: 31
+Part 2: 29
+
+Time beakdowns:
+
+Setup: 1.2ms
+Part 1: 3µs
+Part 2: 4ms
+Total: 5.2ms";
+        let timings = parse_timings(output);
+        assert_eq!(
+            timings,
+            vec![
+                ("Setup".to_string(), Duration::from_micros(1200)),
+                ("Part 1".to_string(), Duration::from_micros(3)),
+                ("Part 2".to_string(), Duration::from_millis(4)),
+                ("Total".to_string(), Duration::from_micros(5200)),
+            ]
+        );
+    }
+
+    #[test]
+    fn day_filters() {
+        assert!(matches_day("aoc-02", "2"));
+        assert!(matches_day("aoc-02", "02"));
+        assert!(matches_day("aoc-02", "aoc-02"));
+        assert!(!matches_day("aoc-12", "2"));
+        assert!(!matches_day("aoc-22", "2"));
+        assert!(!matches_day("aoc-02", "aoc-2x"));
+    }
+
+    #[test]
+    fn summaries_and_history() {
+        let samples: Vec<Duration> = [5, 1, 4, 2]
+            .iter()
+            .map(|n| Duration::from_millis(*n))
+            .collect();
+        let summary = Summary::from_samples(&samples).unwrap();
+        assert_eq!(summary.median, Duration::from_micros(3000));
+        assert_eq!(summary.spread(), Duration::from_millis(4));
+
+        let record = Record {
+            timestamp: 1,
+            year: "2022".to_string(),
+            day: "aoc-12".to_string(),
+            phase: "Part 1".to_string(),
+            runs: 4,
+            summary,
+        };
+        assert_eq!(Record::from_str(&record.to_csv()), Ok(record.clone()));
+
+        let history = History {
+            records: vec![record.clone()],
+        };
+        let previous = history.last("2022", "aoc-12", "Part 1").unwrap();
+        let slower = Summary {
+            median: Duration::from_millis(4),
+            ..summary
+        };
+        assert!(is_regression(&previous.summary, &slower, 10.0));
+        assert!(!is_regression(&previous.summary, &slower, 50.0));
+        assert!(history.last("2022", "aoc-12", "Part 2").is_none());
+    }
+}
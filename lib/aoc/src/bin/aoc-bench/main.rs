@@ -0,0 +1,214 @@
+use aoc::bench::{change, is_regression, matches_day, parse_timings, History, Record, Summary};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{exit, Command};
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const USAGE: &str = "Usage: aoc-bench [--runs N] [--threshold PERCENT] [--history FILE] [--toolchain NAME] [--no-build]
+       YEAR_DIR [DAY...]
+
+Runs each day's release binary against its input.txt N times, reporting the median and spread
+of the Setup/Part 1/Part 2/Total timings it prints along with the wall clock time. Results are
+appended to a CSV history file (YEAR_DIR/bench_history.csv by default) and any phase whose median
+is more than PERCENT slower than the last recorded run is flagged as a regression. Days are given
+by number or binary name, e.g. 2 or aoc-02, and the build uses the rustup toolchain NAME when
+given, such as nightly for years that need it.";
+
+struct Options {
+    runs: usize,
+    threshold: f64,
+    history: Option<PathBuf>,
+    toolchain: Option<String>,
+    build: bool,
+    year_dir: PathBuf,
+    days: Vec<String>,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        runs: 10,
+        threshold: 10.0,
+        history: None,
+        toolchain: None,
+        build: true,
+        year_dir: PathBuf::new(),
+        days: Vec::new(),
+    };
+    let mut positional = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("Missing value for {}", name))
+        };
+        match arg.as_str() {
+            "--runs" => {
+                options.runs = usize::from_str(&value("--runs")?).map_err(|e| e.to_string())?;
+                if options.runs == 0 {
+                    return Err("--runs must be at least 1".to_string());
+                }
+            }
+            "--threshold" => {
+                options.threshold =
+                    f64::from_str(&value("--threshold")?).map_err(|e| e.to_string())?
+            }
+            "--history" => options.history = Some(PathBuf::from(value("--history")?)),
+            "--toolchain" => options.toolchain = Some(value("--toolchain")?),
+            "--no-build" => options.build = false,
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ => positional.push(arg.clone()),
+        }
+    }
+    if positional.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    options.year_dir = PathBuf::from(positional.remove(0));
+    options.days = positional;
+    Ok(options)
+}
+
+fn find_days(year_dir: &Path, filter: &[String]) -> Vec<String> {
+    let mut days: Vec<String> = fs::read_dir(year_dir.join("src/bin"))
+        .expect("Could not read src/bin")
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().join("input.txt").exists())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|day| filter.is_empty() || filter.iter().any(|f| matches_day(day, f)))
+        .collect();
+    days.sort();
+    days
+}
+
+fn run_day(
+    year_dir: &Path,
+    day: &str,
+    runs: usize,
+) -> Result<Vec<(String, Vec<Duration>)>, String> {
+    let binary = year_dir.join("target/release").join(day);
+    let input = year_dir.join("src/bin").join(day).join("input.txt");
+    let mut samples: Vec<(String, Vec<Duration>)> = Vec::new();
+    let mut record =
+        |phase: String, duration: Duration| match samples.iter_mut().find(|(p, _)| *p == phase) {
+            Some((_, durations)) => durations.push(duration),
+            None => samples.push((phase, vec![duration])),
+        };
+    for _ in 0..runs {
+        let start = Instant::now();
+        let output = Command::new(&binary)
+            .arg(&input)
+            .output()
+            .map_err(|e| format!("Could not run {}: {}", binary.display(), e))?;
+        let wall = start.elapsed();
+        if !output.status.success() {
+            return Err(format!("{} exited with {}", day, output.status));
+        }
+        for (phase, duration) in parse_timings(&String::from_utf8_lossy(&output.stdout)) {
+            record(phase, duration);
+        }
+        record("Wall".to_string(), wall);
+    }
+    Ok(samples)
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            exit(2);
+        }
+    };
+    let year_dir = options.year_dir.clone();
+    let year = year_dir
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| year_dir.to_string_lossy().into_owned());
+    let history_path = options
+        .history
+        .clone()
+        .unwrap_or_else(|| year_dir.join("bench_history.csv"));
+
+    if options.build {
+        let mut cargo = Command::new("cargo");
+        if let Some(toolchain) = &options.toolchain {
+            cargo.arg(format!("+{}", toolchain));
+        }
+        let status = cargo
+            .args(["build", "--release", "--bins"])
+            .current_dir(&year_dir)
+            .status()
+            .expect("Could not run cargo");
+        if !status.success() {
+            eprintln!("Build failed");
+            exit(1);
+        }
+    }
+
+    let history = History::load(&history_path).expect("Could not read history");
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let mut records = Vec::new();
+    let mut regressions = Vec::new();
+    for day in find_days(&year_dir, &options.days) {
+        let samples = match run_day(&year_dir, &day, options.runs) {
+            Ok(samples) => samples,
+            Err(message) => {
+                eprintln!("{}: {}", day, message);
+                continue;
+            }
+        };
+        println!("{} ({} runs)", day, options.runs);
+        for (phase, durations) in samples {
+            let summary = Summary::from_samples(&durations).unwrap();
+            let mut line = format!(
+                "  {:<8} median {:>12?}  spread {:>12?}  ({:?}..{:?})",
+                phase,
+                summary.median,
+                summary.spread(),
+                summary.min,
+                summary.max
+            );
+            if let Some(previous) = history.last(&year, &day, &phase) {
+                line += &format!("  {:+.1}%", change(&previous.summary, &summary));
+                if is_regression(&previous.summary, &summary, options.threshold) {
+                    line += "  REGRESSION";
+                    regressions.push(format!("{} {}", day, phase));
+                }
+            }
+            println!("{}", line);
+            records.push(Record {
+                timestamp,
+                year: year.clone(),
+                day: day.clone(),
+                phase,
+                runs: options.runs,
+                summary,
+            });
+        }
+    }
+
+    History::append(&history_path, &records).expect("Could not write history");
+    println!(
+        "\nWrote {} results to {}",
+        records.len(),
+        history_path.display()
+    );
+
+    if !regressions.is_empty() {
+        println!(
+            "\n{} regression(s) beyond {}%: {}",
+            regressions.len(),
+            options.threshold,
+            regressions.join(", ")
+        );
+        exit(1);
+    }
+}
@@ -0,0 +1,221 @@
+use crate::point::Point;
+use std::collections::HashMap;
+
+// An axis-aligned box covering `min` inclusive to `max` exclusive on every axis
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AxisBox<const N: usize> {
+    pub min: Point<N>,
+    pub max: Point<N>,
+}
+
+pub type Rect = AxisBox<2>;
+pub type Cuboid = AxisBox<3>;
+
+impl<const N: usize> AxisBox<N> {
+    pub fn new(min: Point<N>, max: Point<N>) -> AxisBox<N> {
+        AxisBox { min, max }
+    }
+
+    // From the inclusive corners puzzles usually describe boxes with
+    pub fn from_inclusive(min: Point<N>, max: Point<N>) -> AxisBox<N> {
+        AxisBox {
+            min,
+            max: max.map(|n| n + 1),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..N).any(|i| self.min[i] >= self.max[i])
+    }
+
+    pub fn volume(&self) -> i64 {
+        if self.is_empty() {
+            return 0;
+        }
+        (0..N).map(|i| self.max[i] - self.min[i]).product()
+    }
+
+    pub fn contains(&self, point: &Point<N>) -> bool {
+        (0..N).all(|i| self.min[i] <= point[i] && point[i] < self.max[i])
+    }
+
+    pub fn contains_box(&self, other: &AxisBox<N>) -> bool {
+        other.is_empty()
+            || (0..N).all(|i| self.min[i] <= other.min[i] && other.max[i] <= self.max[i])
+    }
+
+    pub fn intersection(&self, other: &AxisBox<N>) -> Option<AxisBox<N>> {
+        let result = AxisBox {
+            min: self.min.componentwise_max(&other.min),
+            max: self.max.componentwise_min(&other.max),
+        };
+        if result.is_empty() {
+            None
+        } else {
+            Some(result)
+        }
+    }
+}
+
+// A region made of boxes, kept as a signed sum of boxes by inclusion–exclusion. Adding a box
+// cancels its overlap with everything already present rather than splitting boxes apart, and
+// identical boxes are merged so repeated overlaps don't pile up.
+#[derive(Clone, Debug, Default)]
+pub struct BoxSet<const N: usize> {
+    terms: HashMap<AxisBox<N>, i64>,
+}
+
+impl<const N: usize> BoxSet<N> {
+    pub fn new() -> BoxSet<N> {
+        BoxSet {
+            terms: HashMap::new(),
+        }
+    }
+
+    fn from_terms(terms: impl IntoIterator<Item = (AxisBox<N>, i64)>) -> BoxSet<N> {
+        let mut set = BoxSet::new();
+        set.extend(terms);
+        set
+    }
+
+    fn extend(&mut self, terms: impl IntoIterator<Item = (AxisBox<N>, i64)>) {
+        for (b, weight) in terms {
+            let total = self.terms.entry(b).or_insert(0);
+            *total += weight;
+            if *total == 0 {
+                self.terms.remove(&b);
+            }
+        }
+    }
+
+    fn overlaps_with<'a>(
+        &'a self,
+        b: &'a AxisBox<N>,
+    ) -> impl Iterator<Item = (AxisBox<N>, i64)> + 'a {
+        self.terms
+            .iter()
+            .filter_map(move |(existing, weight)| existing.intersection(b).map(|i| (i, *weight)))
+    }
+
+    pub fn add(&mut self, b: &AxisBox<N>) {
+        if b.is_empty() {
+            return;
+        }
+        self.subtract(b);
+        self.extend(vec![(*b, 1)]);
+    }
+
+    pub fn subtract(&mut self, b: &AxisBox<N>) {
+        let cancelled: Vec<_> = self
+            .overlaps_with(b)
+            .map(|(overlap, weight)| (overlap, -weight))
+            .collect();
+        self.extend(cancelled);
+    }
+
+    // Keeps only the part of the set inside the box
+    pub fn intersect(&mut self, b: &AxisBox<N>) {
+        *self = BoxSet::from_terms(self.overlaps_with(b));
+    }
+
+    pub fn intersection(&self, other: &BoxSet<N>) -> BoxSet<N> {
+        BoxSet::from_terms(self.terms.iter().flat_map(|(a, wa)| {
+            other
+                .terms
+                .iter()
+                .filter_map(move |(b, wb)| a.intersection(b).map(|i| (i, wa * wb)))
+        }))
+    }
+
+    pub fn union(&self, other: &BoxSet<N>) -> BoxSet<N> {
+        let mut result = self.difference(other);
+        result.extend(other.terms.iter().map(|(b, w)| (*b, *w)));
+        result
+    }
+
+    pub fn difference(&self, other: &BoxSet<N>) -> BoxSet<N> {
+        let mut result = self.clone();
+        result.extend(
+            self.intersection(other)
+                .terms
+                .into_iter()
+                .map(|(b, w)| (b, -w)),
+        );
+        result
+    }
+
+    pub fn volume(&self) -> i64 {
+        self.terms
+            .iter()
+            .map(|(b, weight)| b.volume() * weight)
+            .sum()
+    }
+
+    pub fn contains(&self, point: &Point<N>) -> bool {
+        self.terms
+            .iter()
+            .filter(|(b, _)| b.contains(point))
+            .map(|(_, weight)| weight)
+            .sum::<i64>()
+            > 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.volume() == 0
+    }
+
+    // Number of signed boxes currently used to represent the set
+    pub fn term_count(&self) -> usize {
+        self.terms.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point::{Point2, Point3};
+
+    fn cube(min: (i64, i64, i64), max: (i64, i64, i64)) -> Cuboid {
+        Cuboid::from_inclusive(Point3::from(min), Point3::from(max))
+    }
+
+    #[test]
+    fn reactor_example() {
+        let mut set = BoxSet::new();
+        set.add(&cube((10, 10, 10), (12, 12, 12)));
+        assert_eq!(set.volume(), 27);
+        set.add(&cube((11, 11, 11), (13, 13, 13)));
+        assert_eq!(set.volume(), 27 + 19);
+        set.subtract(&cube((9, 9, 9), (11, 11, 11)));
+        assert_eq!(set.volume(), 27 + 19 - 8);
+        set.add(&cube((10, 10, 10), (10, 10, 10)));
+        assert_eq!(set.volume(), 39);
+        assert!(set.contains(&Point3::new(10, 10, 10)));
+        assert!(!set.contains(&Point3::new(9, 9, 9)));
+
+        set.add(&cube((10, 10, 10), (10, 10, 10)));
+        assert_eq!(set.volume(), 39);
+    }
+
+    #[test]
+    fn rectangles() {
+        let rect = |x1, y1, x2, y2| Rect::new(Point2::new(x1, y1), Point2::new(x2, y2));
+        let mut a = BoxSet::new();
+        a.add(&rect(0, 0, 4, 4));
+        a.add(&rect(2, 2, 6, 6));
+        assert_eq!(a.volume(), 28);
+
+        let mut b = BoxSet::new();
+        b.add(&rect(3, 0, 10, 3));
+        assert_eq!(a.intersection(&b).volume(), 5);
+        assert_eq!(a.union(&b).volume(), 28 + 21 - 5);
+        assert_eq!(a.difference(&b).volume(), 23);
+        assert_eq!(b.difference(&a).volume(), 16);
+
+        a.intersect(&rect(1, 1, 3, 3));
+        assert_eq!(a.volume(), 4);
+        a.subtract(&rect(0, 0, 10, 10));
+        assert!(a.is_empty());
+        assert_eq!(a.term_count(), 0);
+    }
+}
@@ -0,0 +1,462 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Card {
+    pub rank: char,
+    pub suit: Option<char>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Hand {
+    pub cards: Vec<Card>,
+}
+
+// The shapes a hand can take. Each one only asks that the hand contains it, so they work for any
+// hand size, and which beats which is up to the ranking table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Category {
+    HighCard,
+    Pair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+    FiveOfAKind,
+}
+
+// How to order hands that fall in the same category
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TieBreak {
+    // Card by card in the order they were dealt
+    Positional,
+    // Biggest groups first, then highest rank, so the pair in a full house counts after the three
+    BestKicker,
+}
+
+// Where a hand stands: one past its category's index in the ranking table, or zero if it makes
+// none of them, then the ranks used to break ties as indices into the rank order. Comparing two
+// strengths compares the hands.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Strength {
+    pub category: usize,
+    pub ties: Vec<usize>,
+}
+
+// Wild cards take whatever rank and suit makes the best category, but break ties as themselves,
+// so put them at the bottom of the rank order to make them weak.
+#[derive(Clone, Debug)]
+pub struct Rules {
+    // Weakest first
+    ranks: Vec<char>,
+    wild: Vec<char>,
+    hand_size: usize,
+    // Weakest first, with any category not listed never being made
+    categories: Vec<Category>,
+    tie_break: TieBreak,
+    // Whether the top rank can also sit below the bottom one in a straight, as an ace does
+    wheel: bool,
+}
+
+impl Rules {
+    // Five cards ranked on groups of equal ranks alone, compared card by card on a tie
+    pub fn new(ranks: &str) -> Rules {
+        Rules {
+            ranks: ranks.chars().collect(),
+            wild: Vec::new(),
+            hand_size: 5,
+            categories: vec![
+                Category::HighCard,
+                Category::Pair,
+                Category::TwoPair,
+                Category::ThreeOfAKind,
+                Category::FullHouse,
+                Category::FourOfAKind,
+                Category::FiveOfAKind,
+            ],
+            tie_break: TieBreak::Positional,
+            wheel: false,
+        }
+    }
+
+    // Five card poker, with aces high or low in a straight
+    pub fn poker() -> Rules {
+        Rules::new("23456789TJQKA")
+            .with_categories(&[
+                Category::HighCard,
+                Category::Pair,
+                Category::TwoPair,
+                Category::ThreeOfAKind,
+                Category::Straight,
+                Category::Flush,
+                Category::FullHouse,
+                Category::FourOfAKind,
+                Category::StraightFlush,
+            ])
+            .with_tie_break(TieBreak::BestKicker)
+            .with_wheel()
+    }
+
+    pub fn with_wild(mut self, wild: &str) -> Rules {
+        self.wild = wild.chars().collect();
+        self
+    }
+
+    pub fn with_hand_size(mut self, hand_size: usize) -> Rules {
+        self.hand_size = hand_size;
+        self
+    }
+
+    pub fn with_categories(mut self, categories: &[Category]) -> Rules {
+        self.categories = categories.to_vec();
+        self
+    }
+
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Rules {
+        self.tie_break = tie_break;
+        self
+    }
+
+    pub fn with_wheel(mut self) -> Rules {
+        self.wheel = true;
+        self
+    }
+
+    fn rank_index(&self, rank: char) -> Result<usize, String> {
+        self.ranks
+            .iter()
+            .position(|r| *r == rank)
+            .ok_or_else(|| format!("Unknown rank {:?}", rank))
+    }
+
+    // Either bare ranks run together, like "KTJJT", or rank and suit pairs split by spaces, like
+    // "KH TD JS JC TH"
+    pub fn parse_hand(&self, s: &str) -> Result<Hand, String> {
+        let s = s.trim();
+        let cards: Vec<Card> = if s.contains(char::is_whitespace) {
+            s.split_whitespace()
+                .map(|card| {
+                    let mut chars = card.chars();
+                    match (chars.next(), chars.next(), chars.next()) {
+                        (Some(rank), Some(suit), None) => Ok(Card {
+                            rank,
+                            suit: Some(suit),
+                        }),
+                        _ => Err(format!("Bad card: {:?}", card)),
+                    }
+                })
+                .collect::<Result<_, _>>()?
+        } else {
+            s.chars().map(|rank| Card { rank, suit: None }).collect()
+        };
+        for card in &cards {
+            self.rank_index(card.rank)
+                .map_err(|e| format!("{} in {:?}", e, s))?;
+        }
+        if cards.len() != self.hand_size {
+            return Err(format!(
+                "Expected {} cards, got {} in {:?}",
+                self.hand_size,
+                cards.len(),
+                s
+            ));
+        }
+        Ok(Hand { cards })
+    }
+
+    // The natural cards, and how many wild ones there are
+    fn split<'a>(&self, hand: &'a Hand) -> (Vec<&'a Card>, usize) {
+        let (natural, wild): (Vec<&Card>, Vec<&Card>) = hand
+            .cards
+            .iter()
+            .partition(|c| !self.wild.contains(&c.rank));
+        (natural, wild.len())
+    }
+
+    pub fn category(&self, hand: &Hand) -> Option<Category> {
+        let (natural, wild) = self.split(hand);
+        self.categories
+            .iter()
+            .rev()
+            .find(|category| self.can_make(**category, &natural, wild))
+            .copied()
+    }
+
+    // Whether the natural cards plus `wild` wild cards can be made to contain the category
+    fn can_make(&self, category: Category, natural: &[&Card], wild: usize) -> bool {
+        let groups: &[usize] = match category {
+            Category::HighCard => &[],
+            Category::Pair => &[2],
+            Category::TwoPair => &[2, 2],
+            Category::ThreeOfAKind => &[3],
+            Category::FullHouse => &[3, 2],
+            Category::FourOfAKind => &[4],
+            Category::FiveOfAKind => &[5],
+            Category::Straight => return self.straight_top(natural, wild).is_some(),
+            Category::Flush => {
+                return self
+                    .suits(natural)
+                    .iter()
+                    .any(|cards| cards.len() + wild >= 5)
+            }
+            Category::StraightFlush => {
+                return self
+                    .suits(natural)
+                    .iter()
+                    .any(|cards| self.straight_top(cards, wild).is_some())
+            }
+        };
+        // Filling the largest groups from the most common ranks needs the fewest wild cards
+        let mut counts: Vec<usize> = rank_counts(natural).values().copied().collect();
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        let needed: usize = groups
+            .iter()
+            .enumerate()
+            .map(|(i, size)| size.saturating_sub(counts.get(i).copied().unwrap_or(0)))
+            .sum();
+        natural.len() + wild >= groups.iter().sum() && needed <= wild
+    }
+
+    // The rank index of the top card of the highest straight that can be made, where the top of
+    // the wheel is the rank just above the low ace
+    fn straight_top(&self, natural: &[&Card], wild: usize) -> Option<usize> {
+        let present: Vec<bool> = self
+            .ranks
+            .iter()
+            .map(|r| natural.iter().any(|c| c.rank == *r))
+            .collect();
+        // With the wheel the top rank also sits just below the bottom one
+        let mut line = Vec::new();
+        if self.wheel {
+            line.extend(present.last().copied());
+        }
+        line.extend(present);
+        let below = self.wheel as usize;
+        line.windows(5)
+            .rposition(|window| window.iter().filter(|p| !**p).count() <= wild)
+            .map(|start| start + 4 - below)
+    }
+
+    // The natural cards of each suit, plus an empty suit for hands made only of wild cards
+    fn suits<'a>(&self, natural: &[&'a Card]) -> Vec<Vec<&'a Card>> {
+        let mut suits: HashMap<char, Vec<&Card>> = HashMap::new();
+        for card in natural {
+            if let Some(suit) = card.suit {
+                suits.entry(suit).or_default().push(card);
+            }
+        }
+        suits
+            .into_values()
+            .chain(std::iter::once(Vec::new()))
+            .collect()
+    }
+
+    // Fails for a hand holding a rank these rules don't know, such as one parsed by other rules
+    pub fn strength(&self, hand: &Hand) -> Result<Strength, String> {
+        let best = self.category(hand);
+        let category = best.map_or(0, |c| {
+            self.categories.iter().position(|x| *x == c).unwrap() + 1
+        });
+        let mut ties: Vec<usize> = hand
+            .cards
+            .iter()
+            .map(|c| self.rank_index(c.rank))
+            .collect::<Result<_, _>>()?;
+        if self.tie_break == TieBreak::BestKicker {
+            let cards: Vec<&Card> = hand.cards.iter().collect();
+            let counts = rank_counts(&cards);
+            ties.sort_unstable_by_key(|r| std::cmp::Reverse((counts[&self.ranks[*r]], *r)));
+            // A straight is only as good as its top card, so the wheel loses to every other
+            if let Some(top) = best.and_then(|c| self.straight_top_in(hand, c)) {
+                ties = vec![top];
+            }
+        }
+        Ok(Strength { category, ties })
+    }
+
+    // The top card of the straight a hand in `category` makes, if it's a straight at all
+    fn straight_top_in(&self, hand: &Hand, category: Category) -> Option<usize> {
+        let (natural, wild) = self.split(hand);
+        match category {
+            Category::Straight => self.straight_top(&natural, wild),
+            Category::StraightFlush => self
+                .suits(&natural)
+                .iter()
+                .filter_map(|cards| self.straight_top(cards, wild))
+                .max(),
+            _ => None,
+        }
+    }
+
+    pub fn compare(&self, a: &Hand, b: &Hand) -> Result<Ordering, String> {
+        Ok(self.strength(a)?.cmp(&self.strength(b)?))
+    }
+
+    // Sorts the hands weakest first alongside their one-based rank, with equal hands sharing the
+    // lower rank
+    pub fn rank<T>(
+        &self,
+        hands: impl IntoIterator<Item = (Hand, T)>,
+    ) -> Result<Vec<(usize, Hand, T)>, String> {
+        let mut scored: Vec<(Strength, Hand, T)> = hands
+            .into_iter()
+            .map(|(hand, value)| Ok((self.strength(&hand)?, hand, value)))
+            .collect::<Result<_, String>>()?;
+        scored.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut ranked = Vec::with_capacity(scored.len());
+        let mut previous: Option<(Strength, usize)> = None;
+        for (i, (strength, hand, value)) in scored.into_iter().enumerate() {
+            let rank = match &previous {
+                Some((p, rank)) if *p == strength => *rank,
+                _ => i + 1,
+            };
+            ranked.push((rank, hand, value));
+            previous = Some((strength, rank));
+        }
+        Ok(ranked)
+    }
+}
+
+fn rank_counts(cards: &[&Card]) -> HashMap<char, usize> {
+    let mut counts = HashMap::new();
+    for card in cards {
+        *counts.entry(card.rank).or_insert(0) += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn camel_cards() {
+        let hands = ["32T3K", "T55J5", "KK677", "KTJJT", "QQQJA"];
+        let plain = Rules::new("23456789TJQKA");
+        let jokers = Rules::new("J23456789TQKA").with_wild("J");
+        let categories: Vec<Category> = hands
+            .iter()
+            .map(|h| plain.category(&plain.parse_hand(h).unwrap()).unwrap())
+            .collect();
+        assert_eq!(
+            categories,
+            vec![
+                Category::Pair,
+                Category::ThreeOfAKind,
+                Category::TwoPair,
+                Category::TwoPair,
+                Category::ThreeOfAKind
+            ]
+        );
+
+        let order = |rules: &Rules| -> Vec<&str> {
+            rules
+                .rank(hands.iter().map(|h| (rules.parse_hand(h).unwrap(), *h)))
+                .unwrap()
+                .into_iter()
+                .map(|(_, _, h)| h)
+                .collect()
+        };
+        assert_eq!(
+            order(&plain),
+            vec!["32T3K", "KTJJT", "KK677", "T55J5", "QQQJA"]
+        );
+        assert_eq!(
+            order(&jokers),
+            vec!["32T3K", "KK677", "T55J5", "QQQJA", "KTJJT"]
+        );
+        assert_eq!(
+            jokers.category(&jokers.parse_hand("JJJJJ").unwrap()),
+            Some(Category::FiveOfAKind)
+        );
+    }
+
+    #[test]
+    fn poker() {
+        let rules = Rules::poker();
+        let category = |s: &str| rules.category(&rules.parse_hand(s).unwrap()).unwrap();
+        assert_eq!(category("2H 3D 5S 9C KD"), Category::HighCard);
+        assert_eq!(category("AH 2D 3S 4C 5D"), Category::Straight);
+        assert_eq!(category("TH JD QS KC AD"), Category::Straight);
+        assert_eq!(category("QH KD AS 2C 3D"), Category::HighCard);
+        assert_eq!(category("2H 7H 5H 9H KH"), Category::Flush);
+        assert_eq!(category("2H 3H 4H 5H 6H"), Category::StraightFlush);
+        assert_eq!(category("3H 3D 3S 9C 9D"), Category::FullHouse);
+
+        // Kickers decide between hands that positional order would get wrong
+        let a = rules.parse_hand("2H 3D 3S 4C 6D").unwrap();
+        let b = rules.parse_hand("9H 2D 2S 4C 5D").unwrap();
+        assert_eq!(rules.compare(&a, &b), Ok(Ordering::Greater));
+        let positional = Rules::poker().with_tie_break(TieBreak::Positional);
+        assert_eq!(positional.compare(&a, &b), Ok(Ordering::Less));
+        let c = rules.parse_hand("6S 4C 3D 2H 3H").unwrap();
+        assert_eq!(rules.compare(&a, &c), Ok(Ordering::Equal));
+        assert_eq!(positional.compare(&a, &c), Ok(Ordering::Less));
+
+        // The ace plays low in the wheel, so it's the lowest straight of all
+        let compare = |a: &str, b: &str| {
+            rules.compare(&rules.parse_hand(a).unwrap(), &rules.parse_hand(b).unwrap())
+        };
+        assert_eq!(
+            compare("AH 2D 3S 4C 5D", "2H 3D 4S 5C 6D"),
+            Ok(Ordering::Less)
+        );
+        assert_eq!(
+            compare("AH 2H 3H 4H 5H", "2D 3D 4D 5D 6D"),
+            Ok(Ordering::Less)
+        );
+        assert_eq!(
+            compare("AH 2D 3S 4C 5D", "5H 4D 3C 2S AS"),
+            Ok(Ordering::Equal)
+        );
+        assert_eq!(
+            compare("TH JD QS KC AD", "9H TD JS QC KD"),
+            Ok(Ordering::Greater)
+        );
+        assert_eq!(
+            compare("AH 2D 3S 4C 5D", "AH KD 3S 4C 5D"),
+            Ok(Ordering::Greater)
+        );
+
+        let wild = Rules::poker().with_wild("2");
+        let category = |s: &str| wild.category(&wild.parse_hand(s).unwrap()).unwrap();
+        assert_eq!(category("2H 5D 6S 8C 9D"), Category::Straight);
+        assert_eq!(category("2H 5H 6H 8H 9H"), Category::StraightFlush);
+        assert_eq!(category("2H 2D 6S 6C KD"), Category::FourOfAKind);
+        assert_eq!(category("2H 2D 2S 2C 2D"), Category::StraightFlush);
+    }
+
+    #[test]
+    fn ranking() {
+        let rules = Rules::new("123456789").with_hand_size(3);
+        let ranked = rules
+            .rank(vec![
+                (rules.parse_hand("999").unwrap(), 'a'),
+                (rules.parse_hand("123").unwrap(), 'b'),
+                (rules.parse_hand("123").unwrap(), 'c'),
+                (rules.parse_hand("121").unwrap(), 'd'),
+            ])
+            .unwrap();
+        let ranks: Vec<(usize, char)> = ranked.iter().map(|(r, _, v)| (*r, *v)).collect();
+        assert_eq!(ranks, vec![(1, 'b'), (1, 'c'), (3, 'd'), (4, 'a')]);
+        assert!(rules.parse_hand("1234").is_err());
+        assert!(rules.parse_hand("12X").is_err());
+        assert!(rules.parse_hand("1H 2H X").is_err());
+
+        // Hands from other rules, or built by hand, may hold ranks these rules don't know
+        let poker = Rules::poker();
+        let ones = rules.parse_hand("111").unwrap();
+        assert_eq!(poker.strength(&ones), Err("Unknown rank '1'".to_string()));
+        assert!(poker.rank(vec![(ones, 'a')]).is_err());
+        let odd = Hand {
+            cards: vec![Card {
+                rank: 'X',
+                suit: Some('H'),
+            }],
+        };
+        assert!(poker.compare(&odd, &odd).is_err());
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// The sequence of states repeats from index `start` every `length` steps
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    // The earliest index in the sequence holding the same state as index `n`
+    pub fn equivalent(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+}
+
+// Every state up to and including the first repeat, so `states[cycle.start + cycle.length]`
+// matches `states[cycle.start]` by key but may differ in other respects, such as a running total.
+#[derive(Clone, Debug)]
+pub struct Periodic<S> {
+    pub cycle: Cycle,
+    pub states: Vec<S>,
+}
+
+impl<S> Periodic<S> {
+    pub fn state_at(&self, n: usize) -> &S {
+        &self.states[self.cycle.equivalent(n)]
+    }
+
+    // Extrapolates a value that may drift by a fixed amount each time round the cycle, such as the
+    // height of a tower, by adding that drift for each full cycle skipped.
+    pub fn value_at(&self, n: usize, value: impl Fn(&S) -> i64) -> i64 {
+        if n < self.states.len() {
+            return value(&self.states[n]);
+        }
+        let Cycle { start, length } = self.cycle;
+        let drift = value(&self.states[start + length]) - value(&self.states[start]);
+        let cycles = ((n - start) / length) as i64;
+        value(self.state_at(n)) + cycles * drift
+    }
+}
+
+// Runs `step` from `initial`, remembering the key of every state, until a key repeats
+pub fn find_periodic<S, K: Eq + Hash>(
+    initial: S,
+    mut step: impl FnMut(&S) -> S,
+    mut key: impl FnMut(&S) -> K,
+) -> Periodic<S> {
+    let mut seen = HashMap::new();
+    let mut states = vec![initial];
+    loop {
+        let current = states.last().unwrap();
+        let n = states.len() - 1;
+        if let Some(start) = seen.insert(key(current), n) {
+            return Periodic {
+                cycle: Cycle {
+                    start,
+                    length: n - start,
+                },
+                states,
+            };
+        }
+        let next = step(current);
+        states.push(next);
+    }
+}
+
+// Brent's algorithm, which only holds a couple of states at a time so suits large states
+pub fn brent<S: Clone, K: Eq>(
+    initial: S,
+    mut step: impl FnMut(&S) -> S,
+    mut key: impl FnMut(&S) -> K,
+) -> Cycle {
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = key(&initial);
+    let mut hare_state = step(&initial);
+    let mut hare = key(&hare_state);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare;
+            power *= 2;
+            length = 0;
+        }
+        hare_state = step(&hare_state);
+        hare = key(&hare_state);
+        length += 1;
+    }
+
+    let mut tortoise_state = initial.clone();
+    let mut hare_state = initial;
+    for _ in 0..length {
+        hare_state = step(&hare_state);
+    }
+    let mut start = 0;
+    while key(&tortoise_state) != key(&hare_state) {
+        tortoise_state = step(&tortoise_state);
+        hare_state = step(&hare_state);
+        start += 1;
+    }
+    Cycle { start, length }
+}
+
+// Floyd's tortoise and hare
+pub fn floyd<S: Clone, K: Eq>(
+    initial: S,
+    mut step: impl FnMut(&S) -> S,
+    mut key: impl FnMut(&S) -> K,
+) -> Cycle {
+    let mut tortoise = step(&initial);
+    let mut hare = step(&tortoise);
+    while key(&tortoise) != key(&hare) {
+        tortoise = step(&tortoise);
+        let once = step(&hare);
+        hare = step(&once);
+    }
+
+    let mut start = 0;
+    tortoise = initial;
+    while key(&tortoise) != key(&hare) {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    let mut length = 1;
+    hare = step(&tortoise);
+    while key(&tortoise) != key(&hare) {
+        hare = step(&hare);
+        length += 1;
+    }
+    Cycle { start, length }
+}
+
+// The state after `n` steps, skipping whole cycles once one is found
+pub fn state_at<S: Clone, K: Eq + Hash>(
+    initial: S,
+    step: impl FnMut(&S) -> S,
+    key: impl FnMut(&S) -> K,
+    n: usize,
+) -> S {
+    find_periodic(initial, step, key).state_at(n).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 2 -> ...
+    fn step(n: &u64) -> u64 {
+        if *n == 5 {
+            2
+        } else {
+            n + 1
+        }
+    }
+
+    #[test]
+    fn detection() {
+        let expected = Cycle {
+            start: 2,
+            length: 4,
+        };
+        assert_eq!(find_periodic(0, step, |n| *n).cycle, expected);
+        assert_eq!(brent(0, step, |n| *n), expected);
+        assert_eq!(floyd(0, step, |n| *n), expected);
+        assert_eq!(
+            brent(7, |n| (n * n + 1) % 255, |n| *n),
+            find_periodic(7, |n| (n * n + 1) % 255, |n| *n).cycle
+        );
+        assert_eq!(state_at(0, step, |n| *n, 1_000_000_000), 4);
+    }
+
+    #[test]
+    fn drift() {
+        // The total grows by 8 every time round, but only the position is part of the key
+        let periodic = find_periodic(
+            (0, 0),
+            |(n, total)| (step(n), total + 1 + step(n) % 2 * 2),
+            |(n, _)| *n,
+        );
+        let brute = (0..100).fold((0, 0), |(n, total), _| {
+            (step(&n), total + 1 + step(&n) % 2 * 2)
+        });
+        assert_eq!(
+            periodic.value_at(100, |(_, total)| *total as i64),
+            brute.1 as i64
+        );
+        assert_eq!(periodic.value_at(3, |(_, total)| *total as i64), 7);
+    }
+}
@@ -0,0 +1,300 @@
+use crate::parse::{any_char, spaces, take_while1, Input, ParseError};
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::str::FromStr;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr<N> {
+    Number(N),
+    Prefix(char, Box<Expr<N>>),
+    Binary(char, Box<Expr<N>>, Box<Expr<N>>),
+}
+
+impl<N> Expr<N>
+where
+    N: Clone
+        + Default
+        + PartialEq
+        + Add<Output = N>
+        + Sub<Output = N>
+        + Mul<Output = N>
+        + Div<Output = N>
+        + Neg<Output = N>,
+{
+    // Arithmetic on + - * / and prefix -, whatever precedence they were parsed with
+    pub fn eval(&self) -> Result<N, String> {
+        match self {
+            Expr::Number(n) => Ok(n.clone()),
+            Expr::Prefix('-', a) => Ok(-a.eval()?),
+            Expr::Binary(op, a, b) => {
+                let (a, b) = (a.eval()?, b.eval()?);
+                match op {
+                    '+' => Ok(a + b),
+                    '-' => Ok(a - b),
+                    '*' => Ok(a * b),
+                    '/' if b == N::default() => Err("Division by zero".to_string()),
+                    '/' => Ok(a / b),
+                    _ => Err(format!("Can't evaluate {:?}", op)),
+                }
+            }
+            Expr::Prefix(op, _) => Err(format!("Can't evaluate prefix {:?}", op)),
+        }
+    }
+}
+
+// Fully bracketed, so the way the expression was grouped is plain to see
+impl<N: fmt::Display> fmt::Display for Expr<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Number(n) => write!(f, "{}", n),
+            Expr::Prefix(op, a) => write!(f, "({}{})", op, a),
+            Expr::Binary(op, a, b) => write!(f, "({} {} {})", a, op, b),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Assoc {
+    Left,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Token<'a> {
+    Number(&'a str),
+    Operator(char),
+    Open,
+    Close,
+}
+
+// Every token along with where it starts, and where the input ends for errors about running
+// out of it
+fn tokenize(input: Input) -> Result<(Vec<(Token, Input)>, Input), ParseError> {
+    let mut tokens = Vec::new();
+    let (_, mut rest) = spaces(input)?;
+    while !rest.is_empty() {
+        let at = rest;
+        let (token, next) = match take_while1("a number", |c| c.is_ascii_digit())(rest) {
+            Ok((digits, next)) => (Token::Number(digits), next),
+            Err(_) => {
+                let (c, next) = any_char(rest)?;
+                let token = match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    _ => Token::Operator(c),
+                };
+                (token, next)
+            }
+        };
+        tokens.push((token, at));
+        rest = spaces(next)?.1;
+    }
+    Ok((tokens, rest))
+}
+
+// The precedence and associativity of each operator, higher precedence binding tighter. Any
+// operator not in the table is a parse error.
+#[derive(Clone, Debug, Default)]
+pub struct Operators {
+    binary: HashMap<char, (u32, Assoc)>,
+    prefix: HashMap<char, u32>,
+}
+
+impl Operators {
+    pub fn new() -> Operators {
+        Operators::default()
+    }
+
+    // The usual rules: * and / before + and -, all left to right, with unary minus above both
+    pub fn arithmetic() -> Operators {
+        Operators::new()
+            .with_binary('+', 1, Assoc::Left)
+            .with_binary('-', 1, Assoc::Left)
+            .with_binary('*', 2, Assoc::Left)
+            .with_binary('/', 2, Assoc::Left)
+            .with_prefix('-', 3)
+    }
+
+    pub fn with_binary(mut self, op: char, precedence: u32, assoc: Assoc) -> Operators {
+        self.binary.insert(op, (precedence, assoc));
+        self
+    }
+
+    pub fn with_prefix(mut self, op: char, precedence: u32) -> Operators {
+        self.prefix.insert(op, precedence);
+        self
+    }
+
+    pub fn parse<N: FromStr>(&self, input: Input) -> Result<Expr<N>, ParseError>
+    where
+        N::Err: fmt::Display,
+    {
+        let (tokens, end) = tokenize(input)?;
+        let mut parser = Parser {
+            operators: self,
+            tokens,
+            next: 0,
+            end,
+        };
+        let expr = parser.expression(0)?;
+        match parser.peek() {
+            Some((Token::Close, at)) => Err(at.error("Unmatched ')'")),
+            Some((_, at)) => Err(at.expected("an operator")),
+            None => Ok(expr),
+        }
+    }
+
+    pub fn parse_str<N: FromStr>(&self, s: &str) -> Result<Expr<N>, ParseError>
+    where
+        N::Err: fmt::Display,
+    {
+        self.parse(Input::new(s))
+    }
+}
+
+struct Parser<'o, 'a> {
+    operators: &'o Operators,
+    tokens: Vec<(Token<'a>, Input<'a>)>,
+    next: usize,
+    end: Input<'a>,
+}
+
+impl<'o, 'a> Parser<'o, 'a> {
+    fn peek(&self) -> Option<(Token<'a>, Input<'a>)> {
+        self.tokens.get(self.next).copied()
+    }
+
+    fn here(&self) -> Input<'a> {
+        self.peek().map_or(self.end, |(_, at)| at)
+    }
+
+    // Pratt parsing: each operator gets a binding power on either side, doubled so that left
+    // associative operators can hold on to their right operand a little less tightly than the
+    // next operator of the same precedence holds its left
+    fn expression<N: FromStr>(&mut self, min_power: u32) -> Result<Expr<N>, ParseError>
+    where
+        N::Err: fmt::Display,
+    {
+        let (token, at) = self
+            .peek()
+            .ok_or_else(|| self.end.expected("an expression"))?;
+        self.next += 1;
+        let mut lhs = match token {
+            Token::Number(digits) => Expr::Number(
+                digits
+                    .parse()
+                    .map_err(|e| at.error(format!("Bad number {:?}: {}", digits, e)))?,
+            ),
+            Token::Open => {
+                let inner = self.expression(0)?;
+                match self.peek() {
+                    Some((Token::Close, _)) => self.next += 1,
+                    _ => return Err(self.here().expected("')'")),
+                }
+                inner
+            }
+            Token::Operator(op) => match self.operators.prefix.get(&op) {
+                Some(precedence) => {
+                    Expr::Prefix(op, Box::new(self.expression(precedence * 2 + 1)?))
+                }
+                None => return Err(at.error(format!("Unexpected operator {:?}", op))),
+            },
+            Token::Close => return Err(at.expected("an expression")),
+        };
+
+        while let Some((token, at)) = self.peek() {
+            let op = match token {
+                Token::Operator(op) => op,
+                Token::Close => break,
+                _ => return Err(at.expected("an operator")),
+            };
+            let (precedence, assoc) = match self.operators.binary.get(&op) {
+                Some(entry) => *entry,
+                None => return Err(at.error(format!("Unknown operator {:?}", op))),
+            };
+            let (left, right) = match assoc {
+                Assoc::Left => (precedence * 2, precedence * 2 + 1),
+                Assoc::Right => (precedence * 2 + 1, precedence * 2),
+            };
+            if left < min_power {
+                break;
+            }
+            self.next += 1;
+            let rhs = self.expression(right)?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grouping(operators: &Operators, s: &str) -> String {
+        operators.parse_str::<i64>(s).unwrap().to_string()
+    }
+
+    #[test]
+    fn precedence() {
+        let usual = Operators::arithmetic();
+        assert_eq!(grouping(&usual, "1 + 2 * 3 - 4"), "((1 + (2 * 3)) - 4)");
+        assert_eq!(grouping(&usual, "-2 * -(3 + 4)"), "((-2) * (-(3 + 4)))");
+        assert_eq!(grouping(&usual, "8 / 4 / 2"), "((8 / 4) / 2)");
+        assert_eq!(usual.parse_str::<i64>("7 - -3 * 2").unwrap().eval(), Ok(13));
+
+        let powers = Operators::arithmetic().with_binary('^', 4, Assoc::Right);
+        assert_eq!(grouping(&powers, "2 ^ 3 ^ 2 * 5"), "((2 ^ (3 ^ 2)) * 5)");
+        // Unary minus sits below ^, so this is -(2 ^ 2)
+        assert_eq!(grouping(&powers, "-2 ^ 2"), "(-(2 ^ 2))");
+
+        let flat = Operators::new()
+            .with_binary('+', 1, Assoc::Left)
+            .with_binary('*', 1, Assoc::Left);
+        assert_eq!(
+            flat.parse_str::<i64>("1 + 2 * 3 + 4 * 5 + 6")
+                .unwrap()
+                .eval(),
+            Ok(71)
+        );
+        let inverted = Operators::new()
+            .with_binary('+', 2, Assoc::Left)
+            .with_binary('*', 1, Assoc::Left);
+        assert_eq!(
+            inverted.parse_str::<i64>("2 * 3 + (4 * 5)").unwrap().eval(),
+            Ok(46)
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let usual = Operators::arithmetic();
+        let error = |s: &str| usual.parse_str::<i64>(s).unwrap_err().to_string();
+        assert_eq!(
+            error("1 +"),
+            "line 1, column 4: Expected an expression, found end of line"
+        );
+        assert_eq!(
+            error("(1 + 2"),
+            "line 1, column 7: Expected ')', found end of line"
+        );
+        assert_eq!(error("1 + 2)"), "line 1, column 6: Unmatched ')'");
+        assert_eq!(
+            error("1 2"),
+            "line 1, column 3: Expected an operator, found \"2\""
+        );
+        assert_eq!(error("1 % 2"), "line 1, column 3: Unknown operator '%'");
+        assert_eq!(error("* 2"), "line 1, column 1: Unexpected operator '*'");
+        assert!(error("99999999999999999999").starts_with("line 1, column 1: Bad number"));
+
+        let input = Input::new("1 + 1\n2 * (3 +)");
+        let second = input.lines().nth(1).unwrap();
+        let error = usual.parse::<i64>(second).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 9));
+        assert_eq!(
+            usual.parse_str::<i64>("1 / (2 - 2)").unwrap().eval(),
+            Err("Division by zero".to_string())
+        );
+    }
+}
@@ -0,0 +1,479 @@
+use crate::parse::{
+    alt, finish, map, pair, preceded, separated, tag, take_while1, terminated, unsigned, ws, Input,
+    ParseError, ParseResult,
+};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Symbol {
+    Terminal(char),
+    Rule(usize),
+}
+
+// A context-free grammar over numbered rules, written one rule a line like `1: 2 3 | "a"`. Any
+// shape of recursion is fine, left recursion included, as matching uses an Earley parser.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Grammar {
+    rules: HashMap<usize, Vec<Vec<Symbol>>>,
+}
+
+// Some symbols along with where each rule reference was written, so that references to
+// missing rules can be pointed at
+type Located<'a> = (Vec<Symbol>, Vec<(usize, Input<'a>)>);
+
+fn symbols(input: Input) -> ParseResult<Located> {
+    let quoted = map(
+        preceded(
+            tag("\""),
+            terminated(take_while1("a terminal", |c| c != '"'), tag("\"")),
+        ),
+        |s: &str| (s.chars().map(Symbol::Terminal).collect(), Vec::new()),
+    );
+    let reference = |input| {
+        let (n, rest) = unsigned(input)?;
+        Ok((
+            (vec![Symbol::Rule(n as usize)], vec![(n as usize, input)]),
+            rest,
+        ))
+    };
+    ws(alt(quoted, reference))(input)
+}
+
+// Zero or more symbols in a row, so an empty alternative matches the empty string
+fn sequence(input: Input) -> ParseResult<Located> {
+    let mut all: Located = (Vec::new(), Vec::new());
+    let mut rest = input;
+    while let Ok(((symbols, references), after)) = symbols(rest) {
+        all.0.extend(symbols);
+        all.1.extend(references);
+        rest = after;
+    }
+    Ok((all, rest))
+}
+
+fn rule(input: Input) -> ParseResult<(usize, Vec<Located>)> {
+    pair(
+        map(terminated(ws(unsigned), ws(tag(":"))), |n| n as usize),
+        separated(sequence, ws(tag("|"))),
+    )(input)
+}
+
+impl Grammar {
+    pub fn new() -> Grammar {
+        Grammar::default()
+    }
+
+    pub fn parse(input: Input) -> Result<Grammar, ParseError> {
+        let mut grammar = Grammar::new();
+        grammar.add_rules(input)?;
+        Ok(grammar)
+    }
+
+    // Adds or replaces every rule in `input`, checking that everything they refer to exists
+    pub fn add_rules(&mut self, input: Input) -> Result<(), ParseError> {
+        let mut references = Vec::new();
+        let mut rules = self.rules.clone();
+        for line in input.lines().filter(|line| !line.rest().trim().is_empty()) {
+            let (n, alternatives) = finish(rule, line)?;
+            let mut symbols = Vec::new();
+            for (alternative, located) in alternatives {
+                symbols.push(alternative);
+                references.extend(located);
+            }
+            rules.insert(n, symbols);
+        }
+        if let Some((n, at)) = references.iter().find(|(n, _)| !rules.contains_key(n)) {
+            return Err(at.error(format!("Rule {} is never defined", n)));
+        }
+        self.rules = rules;
+        Ok(())
+    }
+
+    pub fn rule(&self, n: usize) -> Option<&[Vec<Symbol>]> {
+        self.rules.get(&n).map(|r| r.as_slice())
+    }
+
+    // The rules that can match the empty string
+    fn nullable(&self) -> HashSet<usize> {
+        let mut nullable = HashSet::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (n, alternatives) in self.rules.iter() {
+                if nullable.contains(n) {
+                    continue;
+                }
+                let empty = alternatives.iter().any(|symbols| {
+                    symbols
+                        .iter()
+                        .all(|s| matches!(s, Symbol::Rule(r) if nullable.contains(r)))
+                });
+                if empty {
+                    nullable.insert(*n);
+                    changed = true;
+                }
+            }
+        }
+        nullable
+    }
+
+    // Earley's algorithm, keeping hold of the chart so that a derivation can be read back out
+    // of it or the point where every possibility died can be found
+    fn chart(&self, start: usize, message: &[char]) -> Chart {
+        let nullable = self.nullable();
+        let mut sets: Vec<Vec<Item>> = vec![Vec::new(); message.len() + 1];
+        let mut seen: Vec<HashSet<Item>> = vec![HashSet::new(); message.len() + 1];
+        let mut completed = HashSet::new();
+        let add = |sets: &mut Vec<Vec<Item>>, seen: &mut Vec<HashSet<Item>>, at: usize, item| {
+            if seen[at].insert(item) {
+                sets[at].push(item);
+            }
+        };
+        for alternative in 0..self.rules.get(&start).map_or(0, |r| r.len()) {
+            add(&mut sets, &mut seen, 0, Item::new(start, alternative, 0));
+        }
+
+        for i in 0..=message.len() {
+            let mut next = 0;
+            while next < sets[i].len() {
+                let item = sets[i][next];
+                next += 1;
+                match self.rules[&item.rule][item.alternative].get(item.dot) {
+                    Some(Symbol::Terminal(c)) => {
+                        if message.get(i) == Some(c) {
+                            add(&mut sets, &mut seen, i + 1, item.advanced());
+                        }
+                    }
+                    Some(Symbol::Rule(r)) => {
+                        for alternative in 0..self.rules[r].len() {
+                            add(&mut sets, &mut seen, i, Item::new(*r, alternative, i));
+                        }
+                        // Nullable rules might finish before anything waiting on them arrives,
+                        // so step over them straight away
+                        if nullable.contains(r) {
+                            add(&mut sets, &mut seen, i, item.advanced());
+                        }
+                    }
+                    None => {
+                        completed.insert((item.rule, item.origin, i));
+                        let waiting: Vec<Item> = sets[item.origin]
+                            .iter()
+                            .filter(|w| {
+                                self.rules[&w.rule][w.alternative].get(w.dot)
+                                    == Some(&Symbol::Rule(item.rule))
+                            })
+                            .map(|w| w.advanced())
+                            .collect();
+                        for w in waiting {
+                            add(&mut sets, &mut seen, i, w);
+                        }
+                    }
+                }
+            }
+        }
+        Chart { sets, completed }
+    }
+
+    pub fn matches(&self, start: usize, message: &str) -> bool {
+        let message: Vec<char> = message.chars().collect();
+        self.chart(start, &message)
+            .completed
+            .contains(&(start, 0, message.len()))
+    }
+
+    pub fn count_matches<'m>(
+        &self,
+        start: usize,
+        messages: impl IntoIterator<Item = &'m str>,
+    ) -> usize {
+        messages
+            .into_iter()
+            .filter(|m| self.matches(start, m))
+            .count()
+    }
+
+    // A parse tree for the whole message, or where it stopped making sense
+    pub fn derive(&self, start: usize, message: &str) -> Result<Tree, Mismatch> {
+        let message: Vec<char> = message.chars().collect();
+        let chart = self.chart(start, &message);
+        let mut stack = Vec::new();
+        if let Some(tree) = self.build(&chart, &message, start, 0, message.len(), &mut stack) {
+            return Ok(tree);
+        }
+
+        // The last position anything got to, and what it wanted to see there
+        let position = (0..=message.len())
+            .rev()
+            .find(|i| !chart.sets[*i].is_empty())
+            .unwrap_or(0);
+        let mut expected: Vec<char> = chart.sets[position]
+            .iter()
+            .filter_map(
+                |item| match self.rules[&item.rule][item.alternative].get(item.dot) {
+                    Some(Symbol::Terminal(c)) => Some(*c),
+                    _ => None,
+                },
+            )
+            .collect();
+        expected.sort_unstable();
+        expected.dedup();
+        Err(Mismatch {
+            position,
+            found: message.get(position).copied(),
+            expected,
+        })
+    }
+
+    // Reads a derivation of `rule` over `from..to` out of the chart, refusing to revisit a span
+    // it's already inside so that cycles like `1: 1 | "a"` can't loop forever
+    fn build(
+        &self,
+        chart: &Chart,
+        message: &[char],
+        rule: usize,
+        from: usize,
+        to: usize,
+        stack: &mut Vec<(usize, usize, usize)>,
+    ) -> Option<Tree> {
+        if !chart.completed.contains(&(rule, from, to)) || stack.contains(&(rule, from, to)) {
+            return None;
+        }
+        stack.push((rule, from, to));
+        let mut result = None;
+        for (alternative, symbols) in self.rules[&rule].iter().enumerate() {
+            if let Some(children) = self.split(chart, message, symbols, from, to, stack) {
+                result = Some(Tree::Rule {
+                    rule,
+                    alternative,
+                    children,
+                });
+                break;
+            }
+        }
+        stack.pop();
+        result
+    }
+
+    // Shares `from..to` out between `symbols`, each rule taking a span the chart says it covers
+    fn split(
+        &self,
+        chart: &Chart,
+        message: &[char],
+        symbols: &[Symbol],
+        from: usize,
+        to: usize,
+        stack: &mut Vec<(usize, usize, usize)>,
+    ) -> Option<Vec<Tree>> {
+        let (first, rest) = match symbols.split_first() {
+            Some(split) => split,
+            None if from == to => return Some(Vec::new()),
+            None => return None,
+        };
+        match first {
+            Symbol::Terminal(c) => {
+                if message.get(from) != Some(c) || from >= to {
+                    return None;
+                }
+                let mut children = self.split(chart, message, rest, from + 1, to, stack)?;
+                children.insert(0, Tree::Terminal(*c));
+                Some(children)
+            }
+            Symbol::Rule(r) => {
+                for end in (from..=to).rev() {
+                    if !chart.completed.contains(&(*r, from, end)) {
+                        continue;
+                    }
+                    if let Some(mut children) = self.split(chart, message, rest, end, to, stack) {
+                        if let Some(tree) = self.build(chart, message, *r, from, end, stack) {
+                            children.insert(0, tree);
+                            return Some(children);
+                        }
+                    }
+                }
+                None
+            }
+        }
+    }
+}
+
+impl FromStr for Grammar {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Grammar, ParseError> {
+        Grammar::parse(Input::new(s))
+    }
+}
+
+// A rule partway through matching: which alternative, how many of its symbols are done, and
+// where in the message it began
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Item {
+    rule: usize,
+    alternative: usize,
+    dot: usize,
+    origin: usize,
+}
+
+impl Item {
+    fn new(rule: usize, alternative: usize, origin: usize) -> Item {
+        Item {
+            rule,
+            alternative,
+            dot: 0,
+            origin,
+        }
+    }
+
+    fn advanced(&self) -> Item {
+        Item {
+            dot: self.dot + 1,
+            ..*self
+        }
+    }
+}
+
+struct Chart {
+    sets: Vec<Vec<Item>>,
+    // Every (rule, from, to) where the rule matches that span of the message
+    completed: HashSet<(usize, usize, usize)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Tree {
+    Terminal(char),
+    Rule {
+        rule: usize,
+        alternative: usize,
+        children: Vec<Tree>,
+    },
+}
+
+// Written as `rule(children)`, so `0(4(a) 5(b))` is rule 0 made of rule 4 matching "a" then rule
+// 5 matching "b"
+impl fmt::Display for Tree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Tree::Terminal(c) => write!(f, "{}", c),
+            Tree::Rule { rule, children, .. } => {
+                write!(f, "{}(", rule)?;
+                for (i, child) in children.iter().enumerate() {
+                    if i > 0
+                        && !matches!(
+                            (child, &children[i - 1]),
+                            (Tree::Terminal(_), Tree::Terminal(_))
+                        )
+                    {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{}", child)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+// Why a message didn't match: the furthest position any reading of it reached, and the
+// characters that could have carried on from there
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub position: usize,
+    pub found: Option<char>,
+    pub expected: Vec<char>,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let expected = match self.expected.len() {
+            0 if self.found.is_some() => "the end of the message".to_string(),
+            0 => "nothing more, as no rule can finish here".to_string(),
+            _ => self
+                .expected
+                .iter()
+                .map(|c| format!("{:?}", c))
+                .collect::<Vec<_>>()
+                .join(" or "),
+        };
+        let found = match self.found {
+            Some(c) => format!("{:?}", c),
+            None => "the end of the message".to_string(),
+        };
+        write!(
+            f,
+            "at position {}: expected {}, found {}",
+            self.position, expected, found
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "0: 4 1 5\n1: 2 3 | 3 2\n2: 4 4 | 5 5\n3: 4 5 | 5 4\n4: \"a\"\n5: \"b\"";
+
+    #[test]
+    fn matching() {
+        let grammar: Grammar = EXAMPLE.parse().unwrap();
+        let messages = ["ababbb", "bababa", "abbbab", "aaabbb", "aaaabbb"];
+        assert_eq!(grammar.count_matches(0, messages.iter().copied()), 2);
+        assert_eq!(
+            grammar.derive(0, "ababbb").unwrap().to_string(),
+            "0(4(a) 1(3(5(b) 4(a)) 2(5(b) 5(b))) 5(b))"
+        );
+
+        let mismatch = grammar.derive(0, "aaabbb").unwrap_err();
+        assert_eq!(mismatch.position, 4);
+        assert_eq!(
+            mismatch.to_string(),
+            "at position 4: expected 'a', found 'b'"
+        );
+        let mismatch = grammar.derive(0, "aaaabbb").unwrap_err();
+        assert_eq!(
+            mismatch.to_string(),
+            "at position 6: expected the end of the message, found 'b'"
+        );
+        assert_eq!(
+            grammar.derive(0, "aba").unwrap_err().to_string(),
+            "at position 3: expected 'a' or 'b', found the end of the message"
+        );
+    }
+
+    #[test]
+    fn recursion() {
+        // Left recursion, right recursion, nesting and an empty alternative
+        let grammar: Grammar = "0: 1 2\n1: 1 \"a\" | \"a\"\n2: \"b\" 2 \"c\" | 3\n3: | \"x\""
+            .parse()
+            .unwrap();
+        assert!(grammar.matches(0, "a"));
+        assert!(grammar.matches(0, "aaabbxcc"));
+        assert!(!grammar.matches(0, "aabbc"));
+        assert_eq!(
+            grammar.derive(0, "aabc").unwrap().to_string(),
+            "0(1(1(a) a) 2(b 2(3()) c))"
+        );
+
+        // Cycles through unit rules still terminate
+        let cyclic: Grammar = "0: 1 | \"a\"\n1: 0".parse().unwrap();
+        assert_eq!(cyclic.derive(0, "a").unwrap().to_string(), "0(a)");
+    }
+
+    #[test]
+    fn errors() {
+        let error = "0: 1 2\n1: \"a\"".parse::<Grammar>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 6: Rule 2 is never defined"
+        );
+        let error = "0: 1 &".parse::<Grammar>().unwrap_err();
+        assert_eq!((error.line, error.column), (1, 6));
+
+        let mut grammar: Grammar = EXAMPLE.parse().unwrap();
+        grammar.add_rules(Input::new("5: \"b\" | 5 5")).unwrap();
+        assert!(grammar.matches(0, "ababbbbb"));
+        assert!(grammar.add_rules(Input::new("5: 6")).is_err());
+        assert_eq!(grammar.rule(5).unwrap().len(), 2);
+    }
+}
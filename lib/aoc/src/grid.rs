@@ -0,0 +1,370 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+const DIRECTIONS_4: [(i64, i64); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+const DIRECTIONS_8: [(i64, i64); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+];
+
+// A dense, row-major grid indexed by (x, y) with (0, 0) in the top left
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid2D<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid2D<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid2D<T>
+    where
+        T: Clone,
+    {
+        Grid2D {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(usize, usize) -> T) -> Grid2D<T> {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+        Grid2D {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Grid2D<T>, String> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+        if let Some(y) = rows.iter().position(|row| row.len() != width) {
+            return Err(format!(
+                "Row {} has length {}, expected {}",
+                y,
+                rows[y].len(),
+                width
+            ));
+        }
+        Ok(Grid2D {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    // Builds a grid from text, one row per line, converting each character with `f`
+    pub fn parse_with<E: fmt::Display>(
+        input: &str,
+        mut f: impl FnMut(char) -> Result<T, E>,
+    ) -> Result<Grid2D<T>, String> {
+        let rows = input
+            .lines()
+            .map(|line| line.trim_end_matches('\r'))
+            .skip_while(|line| line.is_empty())
+            .collect::<Vec<_>>();
+        let end = rows
+            .iter()
+            .rposition(|line| !line.is_empty())
+            .map_or(0, |n| n + 1);
+        let rows = rows[..end]
+            .iter()
+            .enumerate()
+            .map(|(y, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(|(x, c)| {
+                        f(c).map_err(|e| format!("Bad cell {:?} at ({}, {}): {}", c, x, y, e))
+                    })
+                    .collect::<Result<Vec<T>, String>>()
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Grid2D::from_rows(rows)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, x: i64, y: i64) -> bool {
+        x >= 0 && y >= 0 && (x as u64) < self.width as u64 && (y as u64) < self.height as u64
+    }
+
+    fn index_of(&self, x: i64, y: i64) -> Option<usize> {
+        if self.in_bounds(x, y) {
+            Some(y as usize * self.width + x as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, x: i64, y: i64) -> Option<&T> {
+        self.index_of(x, y).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, x: i64, y: i64) -> Option<&mut T> {
+        self.index_of(x, y).map(move |i| &mut self.cells[i])
+    }
+
+    // Returns the previous value, or None if the position is outside the grid
+    pub fn set(&mut self, x: i64, y: i64, value: T) -> Option<T> {
+        self.get_mut(x, y)
+            .map(|cell| std::mem::replace(cell, value))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = ((usize, usize), &mut T)> {
+        self.positions().zip(self.cells.iter_mut())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn find(&self, mut predicate: impl FnMut(&T) -> bool) -> Option<(usize, usize)> {
+        self.iter()
+            .find(|(_, value)| predicate(value))
+            .map(|(pos, _)| pos)
+    }
+
+    // Moves from a position by an offset, returning None when that leaves the grid
+    pub fn step(&self, pos: (usize, usize), offset: (i64, i64)) -> Option<(usize, usize)> {
+        let x = i64::try_from(pos.0).ok()? + offset.0;
+        let y = i64::try_from(pos.1).ok()? + offset.1;
+        self.index_of(x, y).map(|_| (x as usize, y as usize))
+    }
+
+    pub fn neighbours_4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        DIRECTIONS_4
+            .iter()
+            .filter_map(move |offset| self.step(pos, *offset))
+    }
+
+    pub fn neighbours_8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        DIRECTIONS_8
+            .iter()
+            .filter_map(move |offset| self.step(pos, *offset))
+    }
+
+    // Positions visited walking from `pos` (exclusive) in a direction until the edge
+    pub fn ray(
+        &self,
+        pos: (usize, usize),
+        offset: (i64, i64),
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        std::iter::successors(self.step(pos, offset), move |p| self.step(*p, offset))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |y| self.row(y))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "Column {} out of bounds", x);
+        self.cells.iter().skip(x).step_by(self.width.max(1))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid2D<U> {
+        Grid2D {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    pub fn transpose(&self) -> Grid2D<T>
+    where
+        T: Clone,
+    {
+        Grid2D::from_fn(self.height, self.width, |x, y| self[(y, x)].clone())
+    }
+
+    pub fn rotate_clockwise(&self) -> Grid2D<T>
+    where
+        T: Clone,
+    {
+        Grid2D::from_fn(self.height, self.width, |x, y| {
+            self[(y, self.height - 1 - x)].clone()
+        })
+    }
+
+    pub fn rotate_anticlockwise(&self) -> Grid2D<T>
+    where
+        T: Clone,
+    {
+        Grid2D::from_fn(self.height, self.width, |x, y| {
+            self[(self.width - 1 - y, x)].clone()
+        })
+    }
+
+    // Mirrors left to right
+    pub fn flip_horizontal(&self) -> Grid2D<T>
+    where
+        T: Clone,
+    {
+        Grid2D::from_fn(self.width, self.height, |x, y| {
+            self[(self.width - 1 - x, y)].clone()
+        })
+    }
+
+    // Mirrors top to bottom
+    pub fn flip_vertical(&self) -> Grid2D<T>
+    where
+        T: Clone,
+    {
+        Grid2D::from_fn(self.width, self.height, |x, y| {
+            self[(x, self.height - 1 - y)].clone()
+        })
+    }
+
+    pub fn render(&self, mut f: impl FnMut(&T) -> char) -> String {
+        self.rows()
+            .map(|row| row.iter().map(&mut f).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Grid2D<char> {
+    pub fn parse(input: &str) -> Grid2D<char> {
+        input.parse().unwrap()
+    }
+}
+
+impl FromStr for Grid2D<char> {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Grid2D::parse_with(input, Ok::<char, String>)
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid2D<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) out of bounds",
+            x,
+            y
+        );
+        &self.cells[y * self.width + x]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid2D<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) out of bounds",
+            x,
+            y
+        );
+        &mut self.cells[y * self.width + x]
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid2D<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for value in row {
+                write!(f, "{}", value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_and_access() {
+        let grid = Grid2D::parse("#..\n.#.\n");
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get(1, 1), Some(&'#'));
+        assert_eq!(grid.get(-1, 0), None);
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.find(|c| *c == '#'), Some((0, 0)));
+        assert_eq!(grid.to_string(), "#..\n.#.");
+        assert!("##\n#".parse::<Grid2D<char>>().is_err());
+
+        let digits = Grid2D::parse_with("12\n34", |c| c.to_digit(10).ok_or("not a digit")).unwrap();
+        assert_eq!(digits.row(1), &[3, 4]);
+        assert_eq!(digits.column(1).copied().collect::<Vec<_>>(), vec![2, 4]);
+        assert!(Grid2D::parse_with("1x", |c| c.to_digit(10).ok_or("not a digit")).is_err());
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = Grid2D::new(3, 3, 0);
+        assert_eq!(
+            grid.neighbours_4((0, 0)).collect::<Vec<_>>(),
+            vec![(1, 0), (0, 1)]
+        );
+        assert_eq!(grid.neighbours_8((1, 1)).count(), 8);
+        assert_eq!(grid.neighbours_8((2, 2)).count(), 3);
+        assert_eq!(
+            grid.ray((0, 1), (1, 0)).collect::<Vec<_>>(),
+            vec![(1, 1), (2, 1)]
+        );
+    }
+
+    #[test]
+    fn transformations() {
+        let grid = Grid2D::parse("abc\ndef");
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf");
+        assert_eq!(grid.rotate_clockwise().to_string(), "da\neb\nfc");
+        assert_eq!(grid.rotate_anticlockwise().to_string(), "cf\nbe\nad");
+        assert_eq!(grid.flip_horizontal().to_string(), "cba\nfed");
+        assert_eq!(grid.flip_vertical().to_string(), "def\nabc");
+        assert_eq!(
+            grid.rotate_clockwise()
+                .rotate_clockwise()
+                .rotate_clockwise()
+                .rotate_clockwise(),
+            grid
+        );
+        assert_eq!(
+            grid.map(|c| c.to_ascii_uppercase()).render(|c| *c),
+            "ABC\nDEF"
+        );
+    }
+}
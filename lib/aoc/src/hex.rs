@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub};
+
+// Axial coordinates, with the implied third cube coordinate s = -q - r. r increases southwards.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Hex {
+    pub q: i64,
+    pub r: i64,
+}
+
+// Unit steps in anticlockwise order, starting east for pointy-topped hexes and south-east for
+// flat-topped ones
+pub const DIRECTIONS: [Hex; 6] = [
+    Hex { q: 1, r: 0 },
+    Hex { q: 1, r: -1 },
+    Hex { q: 0, r: -1 },
+    Hex { q: -1, r: 0 },
+    Hex { q: -1, r: 1 },
+    Hex { q: 0, r: 1 },
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    // Rows of hexes, with neighbours e, ne, nw, w, sw and se
+    Pointy,
+    // Columns of hexes, with neighbours se, ne, n, nw, sw and s
+    Flat,
+}
+
+impl Hex {
+    pub fn new(q: i64, r: i64) -> Hex {
+        Hex { q, r }
+    }
+
+    pub fn from_cube(q: i64, r: i64, s: i64) -> Hex {
+        assert_eq!(q + r + s, 0, "Cube coordinates must sum to zero");
+        Hex { q, r }
+    }
+
+    pub fn s(&self) -> i64 {
+        -self.q - self.r
+    }
+
+    pub fn cube(&self) -> (i64, i64, i64) {
+        (self.q, self.r, self.s())
+    }
+
+    pub fn length(&self) -> i64 {
+        (self.q.abs() + self.r.abs() + self.s().abs()) / 2
+    }
+
+    pub fn distance(&self, other: &Hex) -> i64 {
+        (*self - *other).length()
+    }
+
+    pub fn neighbours(&self) -> impl Iterator<Item = Hex> {
+        let origin = *self;
+        DIRECTIONS.iter().map(move |d| origin + *d)
+    }
+
+    // Sixty degree turns about the origin, as seen with r increasing down the page
+    pub fn rotate_left(&self) -> Hex {
+        let (q, r, s) = self.cube();
+        Hex::from_cube(-s, -q, -r)
+    }
+
+    pub fn rotate_right(&self) -> Hex {
+        let (q, r, s) = self.cube();
+        Hex::from_cube(-r, -s, -q)
+    }
+
+    pub fn rotate_about(&self, centre: &Hex, left_turns: i64) -> Hex {
+        let mut offset = *self - *centre;
+        for _ in 0..left_turns.rem_euclid(6) {
+            offset = offset.rotate_left();
+        }
+        *centre + offset
+    }
+
+    // Every hex exactly `radius` steps away, going anticlockwise
+    pub fn ring(&self, radius: i64) -> Vec<Hex> {
+        if radius == 0 {
+            return vec![*self];
+        }
+        let mut current = *self + DIRECTIONS[4] * radius;
+        let mut result = Vec::new();
+        for direction in DIRECTIONS.iter() {
+            for _ in 0..radius {
+                result.push(current);
+                current += *direction;
+            }
+        }
+        result
+    }
+
+    // Every hex at most `radius` steps away
+    pub fn within(&self, radius: i64) -> Vec<Hex> {
+        (0..=radius).flat_map(|n| self.ring(n)).collect()
+    }
+}
+
+impl Orientation {
+    pub fn direction_names(&self) -> [&'static str; 6] {
+        match self {
+            Orientation::Pointy => ["e", "ne", "nw", "w", "sw", "se"],
+            Orientation::Flat => ["se", "ne", "n", "nw", "sw", "s"],
+        }
+    }
+
+    pub fn direction(&self, name: &str) -> Option<Hex> {
+        self.direction_names()
+            .iter()
+            .position(|n| *n == name)
+            .map(|i| DIRECTIONS[i])
+    }
+
+    // Reads steps such as `esenee` or `n,ne,s`, with or without separators between them
+    pub fn parse_path(&self, input: &str) -> Result<Vec<Hex>, String> {
+        let mut names = self.direction_names();
+        names.sort_by_key(|n| std::cmp::Reverse(n.len()));
+        let mut rest = input.trim();
+        let mut steps = Vec::new();
+        while !rest.is_empty() {
+            rest = rest.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+            if rest.is_empty() {
+                break;
+            }
+            let name = names
+                .iter()
+                .find(|n| rest.starts_with(*n))
+                .ok_or_else(|| format!("Unknown direction at {:?} in {:?}", rest, input))?;
+            steps.push(self.direction(name).unwrap());
+            rest = &rest[name.len()..];
+        }
+        Ok(steps)
+    }
+
+    // Where to draw a hex in text, using two characters per hex across a row for pointy hexes and
+    // two lines per hex down a column for flat ones
+    fn doubled(&self, hex: &Hex) -> (i64, i64) {
+        match self {
+            Orientation::Pointy => (2 * hex.q + hex.r, hex.r),
+            Orientation::Flat => (2 * hex.q, 2 * hex.r + hex.q),
+        }
+    }
+
+    // Draws the given hexes, filling any others within their bounds with `background`
+    pub fn render(&self, cells: impl IntoIterator<Item = (Hex, char)>, background: char) -> String {
+        let cells: HashMap<(i64, i64), char> = cells
+            .into_iter()
+            .map(|(hex, c)| (self.doubled(&hex), c))
+            .collect();
+        if cells.is_empty() {
+            return String::new();
+        }
+        let min_x = cells.keys().map(|p| p.0).min().unwrap();
+        let max_x = cells.keys().map(|p| p.0).max().unwrap();
+        let min_y = cells.keys().map(|p| p.1).min().unwrap();
+        let max_y = cells.keys().map(|p| p.1).max().unwrap();
+        (min_y..=max_y)
+            .map(|y| {
+                let line: String = (min_x..=max_x)
+                    .map(|x| match cells.get(&(x, y)) {
+                        Some(c) => *c,
+                        None if self.is_centre(x, y) => background,
+                        None => ' ',
+                    })
+                    .collect();
+                line.trim_end().to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn is_centre(&self, x: i64, y: i64) -> bool {
+        match self {
+            Orientation::Pointy => (x - y).rem_euclid(2) == 0,
+            Orientation::Flat => x.rem_euclid(2) == 0 && (y - x / 2).rem_euclid(2) == 0,
+        }
+    }
+}
+
+impl Add for Hex {
+    type Output = Hex;
+
+    fn add(self, other: Hex) -> Hex {
+        Hex::new(self.q + other.q, self.r + other.r)
+    }
+}
+
+impl AddAssign for Hex {
+    fn add_assign(&mut self, other: Hex) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Hex {
+    type Output = Hex;
+
+    fn sub(self, other: Hex) -> Hex {
+        Hex::new(self.q - other.q, self.r - other.r)
+    }
+}
+
+impl Neg for Hex {
+    type Output = Hex;
+
+    fn neg(self) -> Hex {
+        Hex::new(-self.q, -self.r)
+    }
+}
+
+impl Mul<i64> for Hex {
+    type Output = Hex;
+
+    fn mul(self, scale: i64) -> Hex {
+        Hex::new(self.q * scale, self.r * scale)
+    }
+}
+
+impl fmt::Display for Hex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.q, self.r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn geometry() {
+        let origin = Hex::default();
+        let east = DIRECTIONS[0];
+        assert_eq!(east.rotate_left(), DIRECTIONS[1]);
+        assert_eq!(east.rotate_right(), DIRECTIONS[5]);
+        assert_eq!(east.rotate_about(&origin, 6), east);
+        assert_eq!(
+            Hex::new(3, -1).rotate_about(&Hex::new(1, 0), -2).cube(),
+            (0, 2, -2)
+        );
+        assert_eq!(Hex::new(3, -1).distance(&Hex::new(-1, 2)), 4);
+        assert_eq!(origin.neighbours().count(), 6);
+        assert_eq!(origin.ring(2).len(), 12);
+        assert!(origin.ring(2).iter().all(|h| h.length() == 2));
+        assert_eq!(origin.within(3).len(), 37);
+    }
+
+    #[test]
+    fn parsing() {
+        let walk = |path: &str, orientation: Orientation| {
+            orientation
+                .parse_path(path)
+                .unwrap()
+                .into_iter()
+                .fold(Hex::default(), |a, b| a + b)
+        };
+        assert_eq!(walk("nwwswee", Orientation::Pointy), Hex::default());
+        assert_eq!(walk("esew", Orientation::Pointy), DIRECTIONS[5]);
+        assert_eq!(walk("ne,ne,s,s", Orientation::Flat).length(), 2);
+        assert_eq!(walk("se,sw,se,sw,sw", Orientation::Flat).length(), 3);
+        assert!(Orientation::Pointy.parse_path("n").is_err());
+    }
+
+    #[test]
+    fn rendering() {
+        let cells = || Hex::default().neighbours().map(|h| (h, '#'));
+        assert_eq!(
+            Orientation::Pointy.render(cells(), '.'),
+            " # #\n# . #\n # #"
+        );
+        assert_eq!(
+            Orientation::Flat.render(cells(), '.'),
+            "  #\n#   #\n  .\n#   #\n  #"
+        );
+    }
+}
@@ -0,0 +1,277 @@
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{Add, Range, RangeInclusive, Sub};
+
+pub trait Integer: Copy + Ord + Default + Add<Output = Self> + Sub<Output = Self> {
+    fn one() -> Self;
+}
+
+macro_rules! integer {
+    ($($t:ty),*) => {
+        $(impl Integer for $t {
+            fn one() -> Self {
+                1
+            }
+        })*
+    };
+}
+
+integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+// A set of integers stored as sorted, disjoint, non-adjacent half-open ranges
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+pub struct IntervalSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T: Integer> IntervalSet<T> {
+    pub fn new() -> IntervalSet<T> {
+        IntervalSet { ranges: Vec::new() }
+    }
+
+    // Builds a set from ranges in any order, merging any that overlap or touch
+    fn normalise(mut ranges: Vec<Range<T>>) -> IntervalSet<T> {
+        ranges.retain(|r| r.start < r.end);
+        ranges.sort_by_key(|r| r.start);
+        let mut merged: Vec<Range<T>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        IntervalSet { ranges: merged }
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        let mut ranges = std::mem::take(&mut self.ranges);
+        ranges.push(range);
+        *self = IntervalSet::normalise(ranges);
+    }
+
+    pub fn insert_inclusive(&mut self, range: RangeInclusive<T>) {
+        self.insert(*range.start()..*range.end() + T::one());
+    }
+
+    pub fn remove(&mut self, range: Range<T>) {
+        *self = self.difference(&IntervalSet::from(range));
+    }
+
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn inclusive_ranges(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.ranges.iter().map(|r| r.start..=r.end - T::one())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    // Total number of values covered
+    pub fn len(&self) -> T {
+        self.ranges
+            .iter()
+            .fold(T::default(), |total, r| total + (r.end - r.start))
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|r| r.start)
+    }
+
+    pub fn max(&self) -> Option<T> {
+        self.ranges.last().map(|r| r.end - T::one())
+    }
+
+    fn position(&self, value: T) -> Result<usize, usize> {
+        self.ranges.binary_search_by(|r| {
+            if r.end <= value {
+                std::cmp::Ordering::Less
+            } else if r.start > value {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.position(value).is_ok()
+    }
+
+    pub fn contains_range(&self, range: &Range<T>) -> bool {
+        if range.start >= range.end {
+            return true;
+        }
+        match self.position(range.start) {
+            Ok(i) => self.ranges[i].end >= range.end,
+            Err(_) => false,
+        }
+    }
+
+    pub fn is_superset(&self, other: &IntervalSet<T>) -> bool {
+        other.ranges.iter().all(|r| self.contains_range(r))
+    }
+
+    pub fn overlaps(&self, other: &IntervalSet<T>) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        IntervalSet::normalise(
+            self.ranges
+                .iter()
+                .chain(other.ranges.iter())
+                .cloned()
+                .collect(),
+        )
+    }
+
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut result = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let a = &self.ranges[i];
+            let b = &other.ranges[j];
+            let start = a.start.max(b.start);
+            let end = a.end.min(b.end);
+            if start < end {
+                result.push(start..end);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { ranges: result }
+    }
+
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut result = Vec::new();
+        let mut j = 0;
+        for range in &self.ranges {
+            let mut start = range.start;
+            while j < other.ranges.len() && other.ranges[j].end <= start {
+                j += 1;
+            }
+            let mut k = j;
+            while k < other.ranges.len() && other.ranges[k].start < range.end {
+                let cut = &other.ranges[k];
+                if cut.start > start {
+                    result.push(start..cut.start);
+                }
+                start = start.max(cut.end);
+                k += 1;
+            }
+            if start < range.end {
+                result.push(start..range.end);
+            }
+        }
+        IntervalSet { ranges: result }
+    }
+
+    pub fn symmetric_difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        self.difference(other).union(&other.difference(self))
+    }
+
+    // Everything within `bounds` that is not in the set
+    pub fn complement(&self, bounds: Range<T>) -> IntervalSet<T> {
+        IntervalSet::from(bounds).difference(self)
+    }
+
+    // Shifts every value by `delta`, which must be negative to move down
+    pub fn offset(&self, delta: T) -> IntervalSet<T> {
+        IntervalSet {
+            ranges: self
+                .ranges
+                .iter()
+                .map(|r| r.start + delta..r.end + delta)
+                .collect(),
+        }
+    }
+}
+
+impl<T: Integer> From<Range<T>> for IntervalSet<T> {
+    fn from(range: Range<T>) -> Self {
+        IntervalSet::normalise(vec![range])
+    }
+}
+
+impl<T: Integer> From<RangeInclusive<T>> for IntervalSet<T> {
+    fn from(range: RangeInclusive<T>) -> Self {
+        let mut set = IntervalSet::new();
+        set.insert_inclusive(range);
+        set
+    }
+}
+
+impl<T: Integer> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        IntervalSet::normalise(iter.into_iter().collect())
+    }
+}
+
+impl<T: Integer> FromIterator<RangeInclusive<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        IntervalSet::normalise(
+            iter.into_iter()
+                .filter(|r| !r.is_empty())
+                .map(|r| *r.start()..*r.end() + T::one())
+                .collect(),
+        )
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for IntervalSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.ranges.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalising() {
+        let set: IntervalSet<i64> = vec![5..=7, 1..=2, 3..=3, 10..=12, 11..=11]
+            .into_iter()
+            .collect();
+        assert_eq!(set.ranges(), &[1..4, 5..8, 10..13]);
+        assert_eq!(
+            set.inclusive_ranges().collect::<Vec<_>>(),
+            vec![1..=3, 5..=7, 10..=12]
+        );
+        assert_eq!(set.len(), 9);
+        assert_eq!((set.min(), set.max()), (Some(1), Some(12)));
+        assert!(set.contains(3) && !set.contains(4) && set.contains(10) && !set.contains(13));
+        assert!(set.contains_range(&(5..8)) && !set.contains_range(&(5..9)));
+    }
+
+    #[test]
+    fn algebra() {
+        let a: IntervalSet<i32> = vec![0..10, 20..30].into_iter().collect();
+        let b: IntervalSet<i32> = vec![5..25, 28..29].into_iter().collect();
+        assert_eq!(a.union(&b), IntervalSet::from(0..30));
+        assert_eq!(a.intersection(&b).ranges(), &[5..10, 20..25, 28..29]);
+        assert_eq!(a.difference(&b).ranges(), &[0..5, 25..28, 29..30]);
+        assert_eq!(b.difference(&a), IntervalSet::from(10..20));
+        assert_eq!(
+            a.symmetric_difference(&b).ranges(),
+            &[0..5, 10..20, 25..28, 29..30]
+        );
+        assert_eq!(a.complement(-5..35).ranges(), &[-5..0, 10..20, 30..35]);
+        assert_eq!(a.offset(-3).ranges(), &[-3..7, 17..27]);
+        assert!(a.overlaps(&b) && !a.overlaps(&IntervalSet::from(10..20)));
+        assert!(a.is_superset(&IntervalSet::from(21..=29)));
+
+        let mut c = a.clone();
+        c.remove(2..22);
+        assert_eq!(c.ranges(), &[0..2, 22..30]);
+        c.insert(2..22);
+        assert_eq!(c, IntervalSet::from(0..30));
+        assert!(IntervalSet::from(5..5).is_empty());
+    }
+}
@@ -0,0 +1,445 @@
+use crate::grid::Grid2D;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+// One of the eight ways to lay a square down: optionally mirrored left to right, then turned
+// clockwise some number of quarter turns
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Orientation {
+    pub flipped: bool,
+    pub turns: u8,
+}
+
+pub const ORIENTATIONS: [Orientation; 8] = [
+    Orientation {
+        flipped: false,
+        turns: 0,
+    },
+    Orientation {
+        flipped: false,
+        turns: 1,
+    },
+    Orientation {
+        flipped: false,
+        turns: 2,
+    },
+    Orientation {
+        flipped: false,
+        turns: 3,
+    },
+    Orientation {
+        flipped: true,
+        turns: 0,
+    },
+    Orientation {
+        flipped: true,
+        turns: 1,
+    },
+    Orientation {
+        flipped: true,
+        turns: 2,
+    },
+    Orientation {
+        flipped: true,
+        turns: 3,
+    },
+];
+
+impl Orientation {
+    pub fn apply<T: Clone>(&self, grid: &Grid2D<T>) -> Grid2D<T> {
+        let mut result = if self.flipped {
+            grid.flip_horizontal()
+        } else {
+            grid.clone()
+        };
+        for _ in 0..self.turns {
+            result = result.rotate_clockwise();
+        }
+        result
+    }
+}
+
+// Each distinct orientation of `grid`, so a symmetric grid is only tried once per look
+fn distinct_orientations<T: Clone + Eq + Hash>(grid: &Grid2D<T>) -> Vec<(Orientation, Grid2D<T>)> {
+    let mut seen = HashSet::new();
+    ORIENTATIONS
+        .iter()
+        .map(|o| (*o, o.apply(grid)))
+        .filter(|(_, g)| seen.insert(g.clone()))
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Placed<K, T> {
+    pub id: K,
+    pub orientation: Orientation,
+    pub tile: Grid2D<T>,
+}
+
+// Tiles arranged in a square, each turned to fit its neighbours
+#[derive(Clone, Debug, PartialEq)]
+pub struct Layout<K, T> {
+    side: usize,
+    placed: Vec<Placed<K, T>>,
+}
+
+impl<K, T> Layout<K, T> {
+    // The number of tiles along each side
+    pub fn side(&self) -> usize {
+        self.side
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> &Placed<K, T> {
+        &self.placed[y * self.side + x]
+    }
+
+    pub fn corners(&self) -> [&K; 4] {
+        let last = self.side - 1;
+        [
+            &self.get(0, 0).id,
+            &self.get(last, 0).id,
+            &self.get(0, last).id,
+            &self.get(last, last).id,
+        ]
+    }
+
+    // The whole picture, with `border` pixels cut from every side of every tile first
+    pub fn image(&self, border: usize) -> Grid2D<T>
+    where
+        T: Clone,
+    {
+        let inner = self.placed[0].tile.width() - 2 * border;
+        Grid2D::from_fn(self.side * inner, self.side * inner, |x, y| {
+            let tile = &self.get(x / inner, y / inner).tile;
+            tile[(x % inner + border, y % inner + border)].clone()
+        })
+    }
+}
+
+struct Piece<T> {
+    tile: usize,
+    orientation: Orientation,
+    grid: Grid2D<T>,
+    top: Vec<T>,
+    left: Vec<T>,
+    bottom: Vec<T>,
+    right: Vec<T>,
+}
+
+struct Search<T> {
+    side: usize,
+    pieces: Vec<Piece<T>>,
+    by_left: HashMap<Vec<T>, Vec<usize>>,
+    by_top: HashMap<Vec<T>, Vec<usize>>,
+    used: Vec<bool>,
+    placed: Vec<usize>,
+    // One solution for each different way the tiles fit, and every turn of those
+    layouts: Vec<Vec<usize>>,
+    seen: HashSet<Vec<(usize, Grid2D<T>)>>,
+}
+
+impl<T: Clone + Eq + Hash> Search<T> {
+    // Which tile ends up where, and how it looks, once the whole square is turned
+    fn turned(&self, solution: &[usize], orientation: &Orientation) -> Vec<(usize, Grid2D<T>)> {
+        let grid = Grid2D::from_fn(self.side, self.side, |x, y| solution[y * self.side + x]);
+        orientation
+            .apply(&grid)
+            .values()
+            .map(|p| {
+                let piece = &self.pieces[*p];
+                (piece.tile, orientation.apply(&piece.grid))
+            })
+            .collect()
+    }
+
+    fn run(&mut self) {
+        if self.layouts.len() > 1 {
+            return;
+        }
+        let i = self.placed.len();
+        if i == self.side * self.side {
+            // Any layout turns up again turned every way the square can be, fewer for a
+            // symmetric one, so only count solutions that aren't a turn of an earlier one
+            if !self
+                .seen
+                .contains(&self.turned(&self.placed, &ORIENTATIONS[0]))
+            {
+                let turns: Vec<_> = ORIENTATIONS
+                    .iter()
+                    .map(|o| self.turned(&self.placed, o))
+                    .collect();
+                self.seen.extend(turns);
+                self.layouts.push(self.placed.clone());
+            }
+            return;
+        }
+        let (x, y) = (i % self.side, i / self.side);
+        let left = if x > 0 {
+            Some(&self.pieces[self.placed[i - 1]].right)
+        } else {
+            None
+        };
+        let above = if y > 0 {
+            Some(&self.pieces[self.placed[i - self.side]].bottom)
+        } else {
+            None
+        };
+        let candidates: Vec<usize> = match (left, above) {
+            (Some(edge), _) => self.by_left.get(edge).cloned().unwrap_or_default(),
+            (None, Some(edge)) => self.by_top.get(edge).cloned().unwrap_or_default(),
+            (None, None) => (0..self.pieces.len()).collect(),
+        };
+        let candidates: Vec<usize> = candidates
+            .into_iter()
+            .filter(|c| {
+                let piece = &self.pieces[*c];
+                !self.used[piece.tile] && above.is_none_or(|edge| *edge == piece.top)
+            })
+            .collect();
+        for c in candidates {
+            let tile = self.pieces[c].tile;
+            self.used[tile] = true;
+            self.placed.push(c);
+            self.run();
+            self.placed.pop();
+            self.used[tile] = false;
+        }
+    }
+}
+
+// Arranges square tiles into a square so that touching edges match, trying every orientation
+// of every tile. Fails if the tiles can't be fitted together or fit together in more than one
+// way.
+pub fn assemble<K: Clone, T: Clone + Eq + Hash>(
+    tiles: &[(K, Grid2D<T>)],
+) -> Result<Layout<K, T>, String> {
+    let side = (0..=tiles.len())
+        .find(|s| s * s >= tiles.len())
+        .filter(|s| s * s == tiles.len() && *s > 0)
+        .ok_or_else(|| format!("{} tiles can't make a square", tiles.len()))?;
+    let size = tiles[0].1.width();
+    if let Some(i) = tiles
+        .iter()
+        .position(|(_, t)| t.width() != size || t.height() != size)
+    {
+        return Err(format!("Tile {} is not {} by {}", i, size, size));
+    }
+
+    let mut pieces = Vec::new();
+    for (tile, (_, grid)) in tiles.iter().enumerate() {
+        for (orientation, grid) in distinct_orientations(grid) {
+            pieces.push(Piece {
+                tile,
+                orientation,
+                top: grid.row(0).to_vec(),
+                left: grid.column(0).cloned().collect(),
+                bottom: grid.row(size - 1).to_vec(),
+                right: grid.column(size - 1).cloned().collect(),
+                grid,
+            });
+        }
+    }
+    let mut by_left: HashMap<Vec<T>, Vec<usize>> = HashMap::new();
+    let mut by_top: HashMap<Vec<T>, Vec<usize>> = HashMap::new();
+    for (i, piece) in pieces.iter().enumerate() {
+        by_left.entry(piece.left.clone()).or_default().push(i);
+        by_top.entry(piece.top.clone()).or_default().push(i);
+    }
+
+    let mut search = Search {
+        side,
+        pieces,
+        by_left,
+        by_top,
+        used: vec![false; tiles.len()],
+        placed: Vec::new(),
+        layouts: Vec::new(),
+        seen: HashSet::new(),
+    };
+    search.run();
+    if search.layouts.is_empty() {
+        return Err("The tiles can't be fitted together".to_string());
+    }
+    if search.layouts.len() > 1 {
+        return Err("The tiles fit together in more than one way".to_string());
+    }
+
+    let placed = search.layouts[0]
+        .iter()
+        .map(|p| {
+            let piece = &search.pieces[*p];
+            Placed {
+                id: tiles[piece.tile].0.clone(),
+                orientation: piece.orientation,
+                tile: piece.grid.clone(),
+            }
+        })
+        .collect();
+    Ok(Layout { side, placed })
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Match {
+    // How the pattern was turned to fit, and where its top left corner landed
+    pub orientation: Orientation,
+    pub origin: (usize, usize),
+    pub cells: Vec<(usize, usize)>,
+}
+
+// Every place that the set cells of `pattern` all land on cells of `image` where `lit` holds,
+// trying the pattern in all eight orientations. This finds the same matches as searching each
+// orientation of the image, but keeps positions in the image's own coordinates.
+pub fn find_pattern<T>(
+    image: &Grid2D<T>,
+    pattern: &Grid2D<bool>,
+    lit: impl Fn(&T) -> bool,
+) -> Vec<Match> {
+    let mut matches = Vec::new();
+    for (orientation, oriented) in distinct_orientations(pattern) {
+        if oriented.width() > image.width() || oriented.height() > image.height() {
+            continue;
+        }
+        let offsets: Vec<(usize, usize)> = oriented
+            .iter()
+            .filter(|(_, set)| **set)
+            .map(|(pos, _)| pos)
+            .collect();
+        for y in 0..=image.height() - oriented.height() {
+            for x in 0..=image.width() - oriented.width() {
+                let cells: Vec<(usize, usize)> =
+                    offsets.iter().map(|(dx, dy)| (x + dx, y + dy)).collect();
+                if cells.iter().all(|pos| lit(&image[*pos])) {
+                    matches.push(Match {
+                        orientation,
+                        origin: (x, y),
+                        cells,
+                    });
+                }
+            }
+        }
+    }
+    matches
+}
+
+// Every cell covered by at least one match
+pub fn covered(matches: &[Match]) -> HashSet<(usize, usize)> {
+    matches
+        .iter()
+        .flat_map(|m| m.cells.iter().copied())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Lcg;
+
+    fn picture(side: usize, size: usize) -> Grid2D<bool> {
+        let mut random = Lcg::new(12345);
+        let width = side * (size - 1) + 1;
+        Grid2D::from_fn(width, width, |_, _| random.next_u64() >> 62 == 0)
+    }
+
+    // Cuts a picture into `side` by `side` tiles that share their touching edges, then turns
+    // each one a different way
+    fn cut(
+        picture: &Grid2D<bool>,
+        side: usize,
+        size: usize,
+    ) -> (Grid2D<usize>, Vec<(usize, Grid2D<bool>)>) {
+        let ids = Grid2D::from_fn(side, side, |x, y| y * side + x);
+        let tiles = ids
+            .iter()
+            .map(|((tx, ty), id)| {
+                let tile = Grid2D::from_fn(size, size, |x, y| {
+                    picture[(tx * (size - 1) + x, ty * (size - 1) + y)]
+                });
+                (*id, ORIENTATIONS[id % 8].apply(&tile))
+            })
+            .collect();
+        (ids, tiles)
+    }
+
+    #[test]
+    fn orientations() {
+        let grid = Grid2D::parse("ab\ncd");
+        let all: HashSet<String> = ORIENTATIONS
+            .iter()
+            .map(|o| o.apply(&grid).to_string())
+            .collect();
+        assert_eq!(all.len(), 8);
+        let flipped = Orientation {
+            flipped: true,
+            turns: 1,
+        };
+        assert_eq!(flipped.apply(&grid).to_string(), "db\nca");
+        assert_eq!(distinct_orientations(&Grid2D::parse("ab\nba")).len(), 2);
+    }
+
+    #[test]
+    fn assembly() {
+        let (ids, tiles) = cut(&picture(4, 6), 4, 6);
+        let layout = assemble(&tiles).unwrap();
+        assert_eq!(layout.side(), 4);
+        let arranged = Grid2D::from_fn(4, 4, |x, y| layout.get(x, y).id);
+        assert!(ORIENTATIONS.iter().any(|o| o.apply(&ids) == arranged));
+        let mut corners: Vec<usize> = layout.corners().iter().map(|c| **c).collect();
+        corners.sort_unstable();
+        assert_eq!(corners, vec![0, 3, 12, 15]);
+        assert_eq!(layout.image(1).width(), 16);
+
+        let mut broken = tiles.clone();
+        broken[5].1[(0, 0)] = !broken[5].1[(0, 0)];
+        broken[5].1[(5, 5)] = !broken[5].1[(5, 5)];
+        assert!(assemble(&broken).is_err());
+
+        let blank = vec![(0, Grid2D::new(3, 3, false)); 4];
+        assert!(assemble(&blank).is_err());
+        assert!(assemble(&tiles[..3]).is_err());
+    }
+
+    #[test]
+    fn symmetry() {
+        // Mirror the top left tile onto itself, so it has fewer orientations to try
+        let mut mirrored = picture(3, 5);
+        for y in 0..5 {
+            for x in 0..2 {
+                mirrored[(4 - x, y)] = mirrored[(x, y)];
+            }
+        }
+        let (_, tiles) = cut(&mirrored, 3, 5);
+        assert!(distinct_orientations(&tiles[0].1).len() < 8);
+        let layout = assemble(&tiles).unwrap();
+        let mut corners: Vec<usize> = layout.corners().iter().map(|c| **c).collect();
+        corners.sort_unstable();
+        assert_eq!(corners, vec![0, 2, 6, 8]);
+
+        // A lone tile fits every way it can be turned, but that's still only one layout
+        let single = [(7, Grid2D::parse("#.#\n...\n#.#"))];
+        assert_eq!(assemble(&single).unwrap().corners(), [&7; 4]);
+
+        // Mirroring the whole picture lets the two halves swap places
+        let mut symmetric = picture(2, 5);
+        for y in 0..9 {
+            for x in 0..4 {
+                symmetric[(8 - x, y)] = symmetric[(x, y)];
+            }
+        }
+        let (_, tiles) = cut(&symmetric, 2, 5);
+        assert!(assemble(&tiles).is_err());
+    }
+
+    #[test]
+    fn patterns() {
+        let image = Grid2D::parse("#....\n###..\n.....\n..#.#\n..###");
+        let pattern = Grid2D::parse("#..\n###").map(|c| *c == '#');
+        let matches = find_pattern(&image, &pattern, |c| *c == '#');
+        assert_eq!(matches.len(), 3);
+        assert_eq!(matches[0].origin, (0, 0));
+        assert_eq!(matches[1].origin, (2, 3));
+        assert_eq!(matches[2].origin, (2, 3));
+        assert_ne!(matches[1].orientation, matches[2].orientation);
+        assert_eq!(covered(&matches).len(), 9);
+        assert!(find_pattern(&image, &Grid2D::new(6, 1, true), |c| *c == '#').is_empty());
+    }
+}
@@ -0,0 +1,24 @@
+pub mod affine;
+pub mod automaton;
+pub mod balanced;
+pub mod bench;
+pub mod boxes;
+pub mod cards;
+pub mod cycle;
+pub mod expr;
+pub mod grammar;
+pub mod grid;
+pub mod hex;
+pub mod interval;
+pub mod jigsaw;
+pub mod number_theory;
+pub mod ocr;
+pub mod ordering;
+pub mod parse;
+pub mod point;
+pub mod ring;
+pub mod rotation;
+pub mod search;
+#[cfg(test)]
+mod testing;
+pub mod union_find;
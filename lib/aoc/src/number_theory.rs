@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+pub fn gcd(a: i64, b: i64) -> i64 {
+    extended_gcd(a, b).0
+}
+
+// The non-negative lcm, or None if it doesn't fit in an i64
+pub fn checked_lcm(a: i64, b: i64) -> Option<i64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    let (a, b) = (a as i128, b as i128);
+    let lcm = (a / wide_extended_gcd(a, b).0 * b).abs();
+    if lcm > i64::MAX as i128 {
+        return None;
+    }
+    Some(lcm as i64)
+}
+
+// Returns (g, x, y) with a*x + b*y = g, where g is the non-negative gcd of a and b
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (g, x, y) = wide_extended_gcd(a as i128, b as i128);
+    (g as i64, x as i64, y as i64)
+}
+
+// The same in i128, so that crt can work with moduli whose product doesn't fit in an i64
+fn wide_extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+// a * b mod m without overflowing, for any modulus that fits in an i64
+pub fn mulmod(a: i64, b: i64, m: i64) -> i64 {
+    (a as i128 * b as i128).rem_euclid(m as i128) as i64
+}
+
+// a + b mod m, which for values near a large modulus could overflow an i64 on the way
+pub fn addmod(a: i64, b: i64, m: i64) -> i64 {
+    (a as i128 + b as i128).rem_euclid(m as i128) as i64
+}
+
+pub fn modpow(base: i64, mut exponent: u64, m: i64) -> i64 {
+    let mut result = 1_i64.rem_euclid(m);
+    let mut base = base.rem_euclid(m);
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mulmod(result, base, m);
+        }
+        base = mulmod(base, base, m);
+        exponent >>= 1;
+    }
+    result
+}
+
+// The inverse of a modulo m, if a and m are coprime
+pub fn modinverse(a: i64, m: i64) -> Option<i64> {
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    if g == 1 {
+        Some(x.rem_euclid(m))
+    } else {
+        None
+    }
+}
+
+// Solves x = r (mod m) for every (r, m) pair, returning the smallest non-negative x along with
+// the lcm of the moduli. The moduli need not be coprime, but must be positive. Gives None if the
+// congruences contradict each other, a modulus isn't positive, or the lcm doesn't fit in an i64.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    if congruences.iter().any(|&(_, m)| m <= 0) {
+        return None;
+    }
+    let mut x: i128 = 0;
+    let mut modulus: i128 = 1;
+    for &(r, m) in congruences {
+        let (r, m) = (r as i128, m as i128);
+        let (g, p, _) = wide_extended_gcd(modulus, m);
+        let diff = r - x;
+        if diff % g != 0 {
+            return None;
+        }
+        let step = m / g;
+        let k = (diff / g).rem_euclid(step) * p.rem_euclid(step) % step;
+        x += modulus * k;
+        modulus *= step;
+        if modulus > i64::MAX as i128 {
+            return None;
+        }
+        x = x.rem_euclid(modulus);
+    }
+    Some((x as i64, modulus as i64))
+}
+
+// Baby-step giant-step: the smallest n >= 0 with base^n = target (mod m), for base coprime to m
+pub fn discrete_log(base: i64, target: i64, m: i64) -> Option<i64> {
+    let target = target.rem_euclid(m);
+    let steps = (m as f64).sqrt().ceil() as i64;
+    let mut baby = HashMap::new();
+    let mut value = 1_i64.rem_euclid(m);
+    for j in 0..steps {
+        baby.entry(value).or_insert(j);
+        value = mulmod(value, base, m);
+    }
+    let giant = modinverse(modpow(base, steps as u64, m), m)?;
+    let mut value = target;
+    for i in 0..steps {
+        if let Some(j) = baby.get(&value) {
+            return Some(i * steps + j);
+        }
+        value = mulmod(value, giant, m);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(gcd(12, -18), 6);
+        assert_eq!(checked_lcm(4, 6), Some(12));
+        assert_eq!(checked_lcm(-4, 6), Some(12));
+        assert_eq!(checked_lcm(0, 6), Some(0));
+        assert_eq!(checked_lcm(i64::MAX, i64::MAX), Some(i64::MAX));
+        assert_eq!(checked_lcm(i64::MAX - 1, i64::MAX - 2), None);
+        assert_eq!(checked_lcm(i64::MIN, 1), None);
+        let (g, x, y) = extended_gcd(240, 46);
+        assert_eq!((g, 240 * x + 46 * y), (2, 2));
+        assert_eq!(modpow(2, 10, 1000), 24);
+        assert_eq!(modpow(-3, 3, 7), 1);
+        assert_eq!(modinverse(3, 11), Some(4));
+        assert_eq!(modinverse(4, 8), None);
+        let big = 119315717514047;
+        assert_eq!(mulmod(big - 1, big - 1, big), 1);
+        assert_eq!(addmod(i64::MAX - 1, i64::MAX - 2, i64::MAX), i64::MAX - 3);
+    }
+
+    #[test]
+    fn chinese_remainder() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[]), Some((0, 1)));
+        assert_eq!(
+            crt(&[(0, 17), (-2, 13), (-3, 19)]),
+            Some((3417, 17 * 13 * 19))
+        );
+
+        // Moduli past i32 whose lcm still fits, and a pair whose lcm doesn't
+        let (p, q) = (2_147_483_647, 2_147_483_629);
+        let big = p * q;
+        assert_eq!(
+            crt(&[(5, p), (7, q)]).map(|(x, m)| (x % p, x % q, m)),
+            Some((5, 7, big))
+        );
+        assert_eq!(crt(&[(1, big), (2, 4_294_967_291)]), None);
+        assert_eq!(crt(&[(1, 3), (0, 0)]), None);
+        assert_eq!(crt(&[(1, -5)]), None);
+    }
+
+    #[test]
+    fn logarithms() {
+        assert_eq!(discrete_log(7, 5764801, 20201227), Some(8));
+        assert_eq!(discrete_log(7, 17807724, 20201227), Some(11));
+        assert_eq!(discrete_log(2, 1, 11), Some(0));
+        assert_eq!(discrete_log(2, 0, 11), None);
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+
+// The letters puzzles draw, four pixels wide by six tall with a blank column between them
+const SMALL: [(char, &str); 18] = [
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', "###\n.#.\n.#.\n.#.\n.#.\n###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+// The larger six by ten letters, as drawn by converging points of light
+const LARGE: [(char, &str); 14] = [
+    (
+        'A',
+        "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'B',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####.",
+    ),
+    (
+        'C',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####.",
+    ),
+    (
+        'E',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'F',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'G',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#",
+    ),
+    (
+        'H',
+        "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'J',
+        "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###..",
+    ),
+    (
+        'K',
+        "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#",
+    ),
+    (
+        'L',
+        "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'N',
+        "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#",
+    ),
+    (
+        'P',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'R',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#",
+    ),
+    (
+        'X',
+        "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#",
+    ),
+];
+
+// Reads the letters spelled out by a set of lit pixels, whichever way up y increases from the
+// top. Letters are split apart at blank columns, so each must be separated from the next.
+pub fn recognise(pixels: impl IntoIterator<Item = (i64, i64)>) -> Result<String, String> {
+    let pixels: HashSet<(i64, i64)> = pixels.into_iter().collect();
+    if pixels.is_empty() {
+        return Ok(String::new());
+    }
+    let min_x = pixels.iter().map(|p| p.0).min().unwrap();
+    let max_x = pixels.iter().map(|p| p.0).max().unwrap();
+    let min_y = pixels.iter().map(|p| p.1).min().unwrap();
+    let max_y = pixels.iter().map(|p| p.1).max().unwrap();
+    let font: &[(char, &str)] = match max_y - min_y + 1 {
+        6 => &SMALL,
+        10 => &LARGE,
+        height => return Err(format!("No font is {} pixels tall", height)),
+    };
+
+    let column_lit = |x: i64| (min_y..=max_y).any(|y| pixels.contains(&(x, y)));
+    let mut result = String::new();
+    let mut x = min_x;
+    while x <= max_x {
+        if !column_lit(x) {
+            x += 1;
+            continue;
+        }
+        let start = x;
+        while x <= max_x && column_lit(x) {
+            x += 1;
+        }
+        let glyph = (min_y..=max_y)
+            .map(|y| {
+                (start..x)
+                    .map(|x| if pixels.contains(&(x, y)) { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let letter = font
+            .iter()
+            .find(|(_, pattern)| trim_columns(pattern) == glyph)
+            .map(|(letter, _)| *letter)
+            .ok_or_else(|| {
+                format!(
+                    "Unrecognised glyph after {:?}:\n{}",
+                    result,
+                    glyph.replace('.', " ")
+                )
+            })?;
+        result.push(letter);
+    }
+    Ok(result)
+}
+
+// Reads letters from text with `#` or `█` for lit pixels and anything else for unlit ones
+pub fn recognise_str(rendered: &str) -> Result<String, String> {
+    recognise(rendered.lines().enumerate().flat_map(|(y, line)| {
+        line.chars()
+            .enumerate()
+            .filter(|(_, c)| *c == '#' || *c == '█')
+            .map(move |(x, _)| (x as i64, y as i64))
+    }))
+}
+
+// Drops blank columns from either side of a glyph, since letters are split apart at those
+fn trim_columns(pattern: &str) -> String {
+    let rows: Vec<&str> = pattern.lines().collect();
+    let lit = |x: usize| rows.iter().any(|row| row.as_bytes()[x] == b'#');
+    let width = rows[0].len();
+    let start = (0..width).find(|x| lit(*x)).unwrap();
+    let end = (0..width).rev().find(|x| lit(*x)).unwrap() + 1;
+    rows.iter()
+        .map(|row| &row[start..end])
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw(font: &[(char, &str)], text: &str) -> String {
+        let glyph = |letter: char| font.iter().find(|(c, _)| *c == letter).unwrap().1;
+        let height = glyph('L').lines().count();
+        (0..height)
+            .map(|y| {
+                text.chars()
+                    .map(|c| glyph(c).lines().nth(y).unwrap())
+                    .collect::<Vec<_>>()
+                    .join(".")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn small_letters() {
+        let rendered = draw(&SMALL, "FIZZBUZ");
+        assert_eq!(recognise_str(&rendered), Ok("FIZZBUZ".to_string()));
+        assert_eq!(
+            recognise_str(&rendered.replace('#', "█").replace('.', " ")),
+            Ok("FIZZBUZ".to_string())
+        );
+        let error = recognise_str(&rendered.replacen("#", ".", 1)).unwrap_err();
+        assert!(error.starts_with("Unrecognised glyph after \"\""));
+        assert!(recognise_str("#\n#").is_err());
+    }
+
+    #[test]
+    fn large_letters_and_offsets() {
+        let rendered = draw(&LARGE, "HELP");
+        assert_eq!(recognise_str(&rendered), Ok("HELP".to_string()));
+
+        let z = draw(&SMALL, "Z");
+        let pixels = z.lines().enumerate().flat_map(|(y, line)| {
+            line.char_indices()
+                .filter(|(_, c)| *c == '#')
+                .map(move |(x, _)| (x as i64 - 10, y as i64 + 3))
+        });
+        assert_eq!(recognise(pixels), Ok("Z".to_string()));
+    }
+}
@@ -0,0 +1,47 @@
+// Sorts `items` together with some extra `dividers`, returning everything in order along with the
+// one-based position each divider ended up at. Dividers go after any items equal to them.
+pub fn sort_with_dividers<T: Ord + Clone>(
+    items: impl IntoIterator<Item = T>,
+    dividers: &[T],
+) -> (Vec<T>, Vec<usize>) {
+    let mut tagged: Vec<(T, Option<usize>)> = items.into_iter().map(|item| (item, None)).collect();
+    tagged.extend(
+        dividers
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(i, d)| (d, Some(i))),
+    );
+    tagged.sort_by(|a, b| {
+        a.0.cmp(&b.0)
+            .then_with(|| a.1.is_some().cmp(&b.1.is_some()))
+    });
+
+    let mut positions = vec![0; dividers.len()];
+    for (position, (_, divider)) in tagged.iter().enumerate() {
+        if let Some(i) = divider {
+            positions[*i] = position + 1;
+        }
+    }
+    (
+        tagged.into_iter().map(|(item, _)| item).collect(),
+        positions,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dividers() {
+        let (sorted, positions) = sort_with_dividers(vec![5, 1, 4, 2], &[3, 0]);
+        assert_eq!(sorted, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(positions, vec![4, 1]);
+
+        let (sorted, positions) = sort_with_dividers(vec!["b", "a"], &["b"]);
+        assert_eq!(sorted, vec!["a", "b", "b"]);
+        assert_eq!(positions, vec![3]);
+        assert_eq!(sort_with_dividers(Vec::new(), &[1]).1, vec![1]);
+    }
+}
@@ -0,0 +1,482 @@
+use crate::grid::Grid2D;
+use std::fmt;
+
+// Where parsing went wrong, with one-based line and column as an editor would show them
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<ParseError> for String {
+    fn from(error: ParseError) -> String {
+        error.to_string()
+    }
+}
+
+// The unparsed remainder of some text, remembering where it sits in the whole so that errors
+// from a line or section still report positions in the original input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Input<'a> {
+    source: &'a str,
+    start: usize,
+    end: usize,
+}
+
+pub type ParseResult<'a, T> = Result<(T, Input<'a>), ParseError>;
+
+impl<'a> Input<'a> {
+    pub fn new(source: &'a str) -> Input<'a> {
+        Input {
+            source,
+            start: 0,
+            end: source.len(),
+        }
+    }
+
+    pub fn rest(&self) -> &'a str {
+        &self.source[self.start..self.end]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn position(&self) -> (usize, usize) {
+        let before = &self.source[..self.start];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |n| n + 1);
+        (line, before[line_start..].chars().count() + 1)
+    }
+
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        let (line, column) = self.position();
+        ParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    // An error naming what was wanted and what is actually here
+    pub fn expected(&self, wanted: &str) -> ParseError {
+        let found = self.rest().lines().next().unwrap_or("");
+        if found.is_empty() {
+            self.error(format!("Expected {}, found end of line", wanted))
+        } else {
+            self.error(format!("Expected {}, found {:?}", wanted, found))
+        }
+    }
+
+    fn split_at(&self, len: usize) -> (Input<'a>, Input<'a>) {
+        let middle = self.start + len;
+        (
+            Input {
+                end: middle,
+                ..*self
+            },
+            Input {
+                start: middle,
+                ..*self
+            },
+        )
+    }
+
+    fn trim(&self) -> Input<'a> {
+        let rest = self.rest();
+        let start = self.start + (rest.len() - rest.trim_start().len());
+        let end = self.end - (rest.len() - rest.trim_end().len());
+        Input {
+            start,
+            end: end.max(start),
+            ..*self
+        }
+    }
+
+    // Each line, without its line ending
+    pub fn lines(&self) -> impl Iterator<Item = Input<'a>> + 'a {
+        let mut rest = *self;
+        std::iter::from_fn(move || {
+            if rest.is_empty() {
+                return None;
+            }
+            let text = rest.rest();
+            let len = text.find('\n').unwrap_or(text.len());
+            let (line, after) = rest.split_at(len);
+            rest = after.split_at(if after.is_empty() { 0 } else { 1 }).1;
+            let line = match line.rest().strip_suffix('\r') {
+                Some(trimmed) => line.split_at(trimmed.len()).0,
+                None => line,
+            };
+            Some(line)
+        })
+    }
+
+    // Groups of lines separated by blank ones, ignoring blank lines at either end
+    pub fn sections(&self) -> Vec<Input<'a>> {
+        let mut sections = Vec::new();
+        let mut current: Option<(Input<'a>, Input<'a>)> = None;
+        for line in self.lines() {
+            if line.rest().trim().is_empty() {
+                sections.extend(current.take().map(|(first, last)| first.through(&last)));
+            } else {
+                current = Some(match current {
+                    Some((first, _)) => (first, line),
+                    None => (line, line),
+                });
+            }
+        }
+        sections.extend(current.map(|(first, last)| first.through(&last)));
+        sections
+    }
+
+    fn through(&self, last: &Input<'a>) -> Input<'a> {
+        Input {
+            end: last.end,
+            ..*self
+        }
+    }
+}
+
+// Runs `parser` over the whole of `input`, allowing only whitespace to be left over
+pub fn parse_all<'a, T>(
+    parser: impl Fn(Input<'a>) -> ParseResult<'a, T>,
+    input: &'a str,
+) -> Result<T, ParseError> {
+    finish(&parser, Input::new(input))
+}
+
+// Runs `parser` over each non-blank line
+pub fn parse_lines<'a, T>(
+    parser: impl Fn(Input<'a>) -> ParseResult<'a, T>,
+    input: &'a str,
+) -> Result<Vec<T>, ParseError> {
+    Input::new(input)
+        .lines()
+        .filter(|line| !line.rest().trim().is_empty())
+        .map(|line| finish(&parser, line))
+        .collect()
+}
+
+pub fn finish<'a, T>(
+    parser: impl Fn(Input<'a>) -> ParseResult<'a, T>,
+    input: Input<'a>,
+) -> Result<T, ParseError> {
+    let (value, rest) = parser(input)?;
+    if rest.rest().trim().is_empty() {
+        Ok(value)
+    } else {
+        Err(rest.trim().expected("end of input"))
+    }
+}
+
+pub fn tag<'a>(expected: &'static str) -> impl Fn(Input<'a>) -> ParseResult<'a, &'a str> {
+    move |input: Input<'a>| {
+        if input.rest().starts_with(expected) {
+            let (matched, rest) = input.split_at(expected.len());
+            Ok((matched.rest(), rest))
+        } else {
+            Err(input.expected(&format!("{:?}", expected)))
+        }
+    }
+}
+
+// One or more characters matching `predicate`, described as `wanted` in errors
+pub fn take_while1<'a>(
+    wanted: &'static str,
+    predicate: impl Fn(char) -> bool,
+) -> impl Fn(Input<'a>) -> ParseResult<'a, &'a str> {
+    move |input: Input<'a>| {
+        let text = input.rest();
+        let len = text.find(|c| !predicate(c)).unwrap_or(text.len());
+        if len == 0 {
+            return Err(input.expected(wanted));
+        }
+        let (matched, rest) = input.split_at(len);
+        Ok((matched.rest(), rest))
+    }
+}
+
+pub fn word<'a>(input: Input<'a>) -> ParseResult<'a, &'a str> {
+    take_while1("a word", |c: char| c.is_alphanumeric() || c == '_')(input)
+}
+
+pub fn one_of<'a>(options: &'static str) -> impl Fn(Input<'a>) -> ParseResult<'a, char> {
+    move |input: Input<'a>| match input.rest().chars().next() {
+        Some(c) if options.contains(c) => Ok((c, input.split_at(c.len_utf8()).1)),
+        _ => Err(input.expected(&format!("one of {:?}", options))),
+    }
+}
+
+pub fn any_char<'a>(input: Input<'a>) -> ParseResult<'a, char> {
+    match input.rest().chars().next() {
+        Some(c) => Ok((c, input.split_at(c.len_utf8()).1)),
+        None => Err(input.expected("a character")),
+    }
+}
+
+pub fn spaces<'a>(input: Input<'a>) -> ParseResult<'a, ()> {
+    let text = input.rest();
+    let len = text.len() - text.trim_start_matches(&[' ', '\t'][..]).len();
+    Ok(((), input.split_at(len).1))
+}
+
+pub fn signed<'a>(input: Input<'a>) -> ParseResult<'a, i64> {
+    let text = input.rest();
+    let sign = if text.starts_with(&['-', '+'][..]) {
+        1
+    } else {
+        0
+    };
+    let digits = text[sign..]
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len() - sign);
+    if digits == 0 {
+        return Err(input.expected("a number"));
+    }
+    let (number, rest) = input.split_at(sign + digits);
+    number
+        .rest()
+        .parse()
+        .map(|n| (n, rest))
+        .map_err(|e| input.error(format!("Bad number {:?}: {}", number.rest(), e)))
+}
+
+pub fn unsigned<'a>(input: Input<'a>) -> ParseResult<'a, u64> {
+    let (digits, rest) = take_while1("a number", |c: char| c.is_ascii_digit())(input)?;
+    digits
+        .parse()
+        .map(|n| (n, rest))
+        .map_err(|e| input.error(format!("Bad number {:?}: {}", digits, e)))
+}
+
+// Skips spaces and tabs before `parser`
+pub fn ws<'a, T>(
+    parser: impl Fn(Input<'a>) -> ParseResult<'a, T>,
+) -> impl Fn(Input<'a>) -> ParseResult<'a, T> {
+    move |input: Input<'a>| parser(spaces(input)?.1)
+}
+
+pub fn map<'a, T, U>(
+    parser: impl Fn(Input<'a>) -> ParseResult<'a, T>,
+    f: impl Fn(T) -> U,
+) -> impl Fn(Input<'a>) -> ParseResult<'a, U> {
+    move |input: Input<'a>| parser(input).map(|(value, rest)| (f(value), rest))
+}
+
+pub fn pair<'a, T, U>(
+    first: impl Fn(Input<'a>) -> ParseResult<'a, T>,
+    second: impl Fn(Input<'a>) -> ParseResult<'a, U>,
+) -> impl Fn(Input<'a>) -> ParseResult<'a, (T, U)> {
+    move |input: Input<'a>| {
+        let (a, rest) = first(input)?;
+        let (b, rest) = second(rest)?;
+        Ok(((a, b), rest))
+    }
+}
+
+pub fn preceded<'a, T, U>(
+    first: impl Fn(Input<'a>) -> ParseResult<'a, T>,
+    second: impl Fn(Input<'a>) -> ParseResult<'a, U>,
+) -> impl Fn(Input<'a>) -> ParseResult<'a, U> {
+    map(pair(first, second), |(_, b)| b)
+}
+
+pub fn terminated<'a, T, U>(
+    first: impl Fn(Input<'a>) -> ParseResult<'a, T>,
+    second: impl Fn(Input<'a>) -> ParseResult<'a, U>,
+) -> impl Fn(Input<'a>) -> ParseResult<'a, T> {
+    map(pair(first, second), |(a, _)| a)
+}
+
+// Tries `first` then `second`, reporting whichever failure got further if both do
+pub fn alt<'a, T>(
+    first: impl Fn(Input<'a>) -> ParseResult<'a, T>,
+    second: impl Fn(Input<'a>) -> ParseResult<'a, T>,
+) -> impl Fn(Input<'a>) -> ParseResult<'a, T> {
+    move |input: Input<'a>| {
+        first(input).or_else(|a| {
+            second(input).map_err(|b| {
+                if (b.line, b.column) > (a.line, a.column) {
+                    b
+                } else {
+                    a
+                }
+            })
+        })
+    }
+}
+
+pub fn optional<'a, T>(
+    parser: impl Fn(Input<'a>) -> ParseResult<'a, T>,
+) -> impl Fn(Input<'a>) -> ParseResult<'a, Option<T>> {
+    move |input: Input<'a>| match parser(input) {
+        Ok((value, rest)) => Ok((Some(value), rest)),
+        Err(_) => Ok((None, input)),
+    }
+}
+
+// One or more of `item` with `separator` between each
+pub fn separated<'a, T, S>(
+    item: impl Fn(Input<'a>) -> ParseResult<'a, T>,
+    separator: impl Fn(Input<'a>) -> ParseResult<'a, S>,
+) -> impl Fn(Input<'a>) -> ParseResult<'a, Vec<T>> {
+    move |input: Input<'a>| {
+        let (first, mut rest) = item(input)?;
+        let mut items = vec![first];
+        while let Ok((_, after)) = separator(rest) {
+            let (next, after) = item(after)?;
+            items.push(next);
+            rest = after;
+        }
+        Ok((items, rest))
+    }
+}
+
+// Signed numbers such as `1, -2, 3` or `1 -2 3`, with any spaces around the separator
+pub fn number_list<'a>(separator: char) -> impl Fn(Input<'a>) -> ParseResult<'a, Vec<i64>> {
+    move |input: Input<'a>| {
+        let separator = move |input: Input<'a>| {
+            let (_, after) = spaces(input)?;
+            if separator == ' ' {
+                return match after.start > input.start && !after.is_empty() {
+                    true => Ok(((), after)),
+                    false => Err(input.expected("a space")),
+                };
+            }
+            match after.rest().strip_prefix(separator) {
+                Some(_) => Ok(((), after.split_at(separator.len_utf8()).1)),
+                None => Err(after.expected(&format!("{:?}", separator))),
+            }
+        };
+        separated(ws(signed), separator)(input)
+    }
+}
+
+// Lines of `key: value`, with each value left unparsed so that errors from it keep their
+// position in the input
+pub fn key_values<'a>(input: Input<'a>) -> Result<Vec<(&'a str, Input<'a>)>, ParseError> {
+    input
+        .lines()
+        .filter(|line| !line.rest().trim().is_empty())
+        .map(|line| {
+            let line = line.trim();
+            let colon = line
+                .rest()
+                .find(':')
+                .ok_or_else(|| line.expected("\"key: value\""))?;
+            let (key, value) = line.split_at(colon);
+            Ok((key.rest().trim(), value.split_at(1).1.trim()))
+        })
+        .collect()
+}
+
+// A rectangular grid with one row per line, converting each character with `f`
+pub fn grid_with<'a, T>(
+    input: Input<'a>,
+    mut f: impl FnMut(char) -> Result<T, String>,
+) -> Result<Grid2D<T>, ParseError> {
+    let lines: Vec<Input<'a>> = input.lines().collect();
+    let first = lines.iter().position(|l| !l.is_empty()).unwrap_or(0);
+    let last = lines
+        .iter()
+        .rposition(|l| !l.is_empty())
+        .map_or(0, |n| n + 1);
+    let mut rows: Vec<Vec<T>> = Vec::new();
+    for line in lines[first..last].iter() {
+        let mut row = Vec::new();
+        for (i, c) in line.rest().char_indices() {
+            let cell = line.split_at(i).1;
+            row.push(f(c).map_err(|e| cell.error(e))?);
+        }
+        if let Some(first) = rows.first() {
+            if row.len() != first.len() {
+                return Err(line.error(format!(
+                    "Row has length {}, expected {}",
+                    row.len(),
+                    first.len()
+                )));
+            }
+        }
+        rows.push(row);
+    }
+    Ok(Grid2D::from_rows(rows).unwrap())
+}
+
+pub fn char_grid(input: Input) -> Result<Grid2D<char>, ParseError> {
+    grid_with(input, Ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combinators() {
+        let assignment = pair(terminated(word, ws(tag("="))), ws(signed));
+        assert_eq!(parse_all(&assignment, "x = -12 "), Ok(("x", -12)));
+        assert_eq!(parse_all(number_list(','), "1, -2 ,3"), Ok(vec![1, -2, 3]));
+        assert_eq!(parse_all(number_list(' '), "4  5 6"), Ok(vec![4, 5, 6]));
+
+        let error = parse_lines(&assignment, "a = 1\n\nb = 2\nc = x").unwrap_err();
+        assert_eq!((error.line, error.column), (4, 5));
+        assert_eq!(
+            error.to_string(),
+            "line 4, column 5: Expected a number, found \"x\""
+        );
+
+        let op = alt(map(tag("+"), |_| '+'), one_of("*/"));
+        assert_eq!(parse_all(&op, "/"), Ok('/'));
+        assert!(parse_all(&op, "-").is_err());
+        assert_eq!(parse_all(pair(any_char, any_char), "-é"), Ok(('-', 'é')));
+        assert!(parse_all(any_char, "").is_err());
+        assert_eq!(parse_all(optional(signed), "").unwrap(), None);
+        let error = parse_all(signed, "99999999999999999999").unwrap_err();
+        assert!(error.message.starts_with("Bad number"));
+    }
+
+    #[test]
+    fn sections_and_key_values() {
+        let input = "\nname: a\nitems: 1, 2\n\n\nname: b\nitems: 3, q\n";
+        let sections = Input::new(input).sections();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[1].position(), (6, 1));
+
+        let fields = key_values(sections[0]).unwrap();
+        assert_eq!(fields[0].0, "name");
+        assert_eq!(fields[1].1.rest(), "1, 2");
+        assert_eq!(finish(number_list(','), fields[1].1), Ok(vec![1, 2]));
+
+        let items = key_values(sections[1]).unwrap()[1].1;
+        let error = finish(number_list(','), items).unwrap_err();
+        assert_eq!((error.line, error.column), (7, 11));
+        assert!(key_values(Input::new("no colon")).is_err());
+    }
+
+    #[test]
+    fn grids() {
+        let grid = char_grid(Input::new("\n#.\n.#\n")).unwrap();
+        assert_eq!((grid.width(), grid.height()), (2, 2));
+        assert_eq!(grid[(1, 1)], '#');
+
+        let error = char_grid(Input::new("##\n#\n")).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+
+        let digits = |c: char| c.to_digit(10).ok_or(format!("{:?} is not a digit", c));
+        let error = grid_with(Input::new("12\n3x"), digits).unwrap_err();
+        assert_eq!(error.to_string(), "line 2, column 2: 'x' is not a digit");
+    }
+}
@@ -0,0 +1,366 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+// Points and vectors share a representation, so positions can be offset and differenced freely
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point<const N: usize>(pub [i64; N]);
+
+pub type Vector<const N: usize> = Point<N>;
+pub type Point2 = Point<2>;
+pub type Point3 = Point<3>;
+
+impl<const N: usize> Point<N> {
+    pub fn zero() -> Point<N> {
+        Point([0; N])
+    }
+
+    pub fn manhattan(&self, other: &Point<N>) -> i64 {
+        (*self - *other).0.iter().map(|n| n.abs()).sum()
+    }
+
+    pub fn chebyshev(&self, other: &Point<N>) -> i64 {
+        (*self - *other)
+            .0
+            .iter()
+            .map(|n| n.abs())
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn euclidean_squared(&self, other: &Point<N>) -> i64 {
+        let d = *self - *other;
+        d.dot(&d)
+    }
+
+    pub fn euclidean(&self, other: &Point<N>) -> f64 {
+        (self.euclidean_squared(other) as f64).sqrt()
+    }
+
+    pub fn dot(&self, other: &Point<N>) -> i64 {
+        self.0.iter().zip(other.0.iter()).map(|(a, b)| a * b).sum()
+    }
+
+    pub fn signum(&self) -> Point<N> {
+        self.map(i64::signum)
+    }
+
+    pub fn map(&self, f: impl Fn(i64) -> i64) -> Point<N> {
+        let mut result = *self;
+        result.0.iter_mut().for_each(|n| *n = f(*n));
+        result
+    }
+
+    // Component-wise minimum and maximum, useful for bounding boxes
+    pub fn componentwise_min(&self, other: &Point<N>) -> Point<N> {
+        let mut result = *self;
+        (0..N).for_each(|i| result.0[i] = result.0[i].min(other.0[i]));
+        result
+    }
+
+    pub fn componentwise_max(&self, other: &Point<N>) -> Point<N> {
+        let mut result = *self;
+        (0..N).for_each(|i| result.0[i] = result.0[i].max(other.0[i]));
+        result
+    }
+
+    // Unit steps along each axis
+    pub fn orthogonal_directions() -> Vec<Vector<N>> {
+        (0..N)
+            .flat_map(|axis| {
+                [-1, 1].iter().map(move |step| {
+                    let mut v = Point::zero();
+                    v.0[axis] = *step;
+                    v
+                })
+            })
+            .collect()
+    }
+
+    // Every offset with components in -1..=1 other than zero, 3^N - 1 of them
+    pub fn all_directions() -> Vec<Vector<N>> {
+        let count = 3_usize.pow(N as u32);
+        (0..count)
+            .map(|mut n| {
+                let mut v = Point::zero();
+                for component in v.0.iter_mut() {
+                    *component = (n % 3) as i64 - 1;
+                    n /= 3;
+                }
+                v
+            })
+            .filter(|v| *v != Point::zero())
+            .collect()
+    }
+
+    pub fn orthogonal_neighbours(&self) -> impl Iterator<Item = Point<N>> {
+        let origin = *self;
+        Point::orthogonal_directions()
+            .into_iter()
+            .map(move |d| origin + d)
+    }
+
+    pub fn neighbours(&self) -> impl Iterator<Item = Point<N>> {
+        let origin = *self;
+        Point::all_directions().into_iter().map(move |d| origin + d)
+    }
+}
+
+impl Point<2> {
+    pub fn new(x: i64, y: i64) -> Point2 {
+        Point([x, y])
+    }
+
+    pub fn x(&self) -> i64 {
+        self.0[0]
+    }
+
+    pub fn y(&self) -> i64 {
+        self.0[1]
+    }
+
+    // Quarter turns about the origin with y pointing up; with y pointing down, as in most
+    // puzzle maps, left and right swap.
+    pub fn rotate_left(&self) -> Point2 {
+        Point([-self.y(), self.x()])
+    }
+
+    pub fn rotate_right(&self) -> Point2 {
+        Point([self.y(), -self.x()])
+    }
+
+    pub fn rotate_about(&self, centre: &Point2, left_turns: i64) -> Point2 {
+        let mut offset = *self - *centre;
+        for _ in 0..left_turns.rem_euclid(4) {
+            offset = offset.rotate_left();
+        }
+        *centre + offset
+    }
+}
+
+impl Point<3> {
+    pub fn new(x: i64, y: i64, z: i64) -> Point3 {
+        Point([x, y, z])
+    }
+
+    pub fn x(&self) -> i64 {
+        self.0[0]
+    }
+
+    pub fn y(&self) -> i64 {
+        self.0[1]
+    }
+
+    pub fn z(&self) -> i64 {
+        self.0[2]
+    }
+
+    // Right-handed quarter turns about each axis
+    pub fn rotate_x(&self) -> Point3 {
+        Point([self.x(), -self.z(), self.y()])
+    }
+
+    pub fn rotate_y(&self) -> Point3 {
+        Point([self.z(), self.y(), -self.x()])
+    }
+
+    pub fn rotate_z(&self) -> Point3 {
+        Point([-self.y(), self.x(), self.z()])
+    }
+
+    pub fn cross(&self, other: &Point3) -> Point3 {
+        Point([
+            self.y() * other.z() - self.z() * other.y(),
+            self.z() * other.x() - self.x() * other.z(),
+            self.x() * other.y() - self.y() * other.x(),
+        ])
+    }
+}
+
+impl<const N: usize> Default for Point<N> {
+    fn default() -> Self {
+        Point::zero()
+    }
+}
+
+impl<const N: usize> Add for Point<N> {
+    type Output = Point<N>;
+
+    fn add(mut self, other: Point<N>) -> Point<N> {
+        self += other;
+        self
+    }
+}
+
+impl<const N: usize> AddAssign for Point<N> {
+    fn add_assign(&mut self, other: Point<N>) {
+        (0..N).for_each(|i| self.0[i] += other.0[i]);
+    }
+}
+
+impl<const N: usize> Sub for Point<N> {
+    type Output = Point<N>;
+
+    fn sub(mut self, other: Point<N>) -> Point<N> {
+        self -= other;
+        self
+    }
+}
+
+impl<const N: usize> SubAssign for Point<N> {
+    fn sub_assign(&mut self, other: Point<N>) {
+        (0..N).for_each(|i| self.0[i] -= other.0[i]);
+    }
+}
+
+impl<const N: usize> Neg for Point<N> {
+    type Output = Point<N>;
+
+    fn neg(self) -> Point<N> {
+        self.map(|n| -n)
+    }
+}
+
+impl<const N: usize> Mul<i64> for Point<N> {
+    type Output = Point<N>;
+
+    fn mul(self, scale: i64) -> Point<N> {
+        self.map(|n| n * scale)
+    }
+}
+
+impl<const N: usize> Index<usize> for Point<N> {
+    type Output = i64;
+
+    fn index(&self, axis: usize) -> &i64 {
+        &self.0[axis]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for Point<N> {
+    fn index_mut(&mut self, axis: usize) -> &mut i64 {
+        &mut self.0[axis]
+    }
+}
+
+impl<const N: usize> From<[i64; N]> for Point<N> {
+    fn from(components: [i64; N]) -> Self {
+        Point(components)
+    }
+}
+
+impl From<(i64, i64)> for Point2 {
+    fn from((x, y): (i64, i64)) -> Self {
+        Point([x, y])
+    }
+}
+
+impl From<Point2> for (i64, i64) {
+    fn from(p: Point2) -> Self {
+        (p.x(), p.y())
+    }
+}
+
+impl From<(i64, i64, i64)> for Point3 {
+    fn from((x, y, z): (i64, i64, i64)) -> Self {
+        Point([x, y, z])
+    }
+}
+
+impl From<Point3> for (i64, i64, i64) {
+    fn from(p: Point3) -> Self {
+        (p.x(), p.y(), p.z())
+    }
+}
+
+// Parses comma separated components such as `1,-2,3`, ignoring surrounding whitespace
+impl<const N: usize> FromStr for Point<N> {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let components = input
+            .trim()
+            .split(',')
+            .map(|s| {
+                s.trim()
+                    .parse::<i64>()
+                    .map_err(|e| format!("Bad component {:?} in {:?}: {}", s, input, e))
+            })
+            .collect::<Result<Vec<i64>, String>>()?;
+        if components.len() != N {
+            return Err(format!(
+                "Expected {} components, found {} in {:?}",
+                N,
+                components.len(),
+                input
+            ));
+        }
+        let mut point = Point::zero();
+        point.0.copy_from_slice(&components);
+        Ok(point)
+    }
+}
+
+impl<const N: usize> fmt::Display for Point<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, n) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", n)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic_and_distances() {
+        let a = Point3::new(1, 2, 3);
+        let b = Point3::new(-2, 6, 3);
+        assert_eq!(a + b, Point3::new(-1, 8, 6));
+        assert_eq!(b - a, Point3::new(-3, 4, 0));
+        assert_eq!(-a * 2, Point3::new(-2, -4, -6));
+        assert_eq!(a.manhattan(&b), 7);
+        assert_eq!(a.chebyshev(&b), 4);
+        assert_eq!(a.euclidean(&b), 5.0);
+        assert_eq!((b - a).signum(), Point3::new(-1, 1, 0));
+        assert_eq!(
+            Point3::new(1, 0, 0).cross(&Point3::new(0, 1, 0)),
+            Point3::new(0, 0, 1)
+        );
+    }
+
+    #[test]
+    fn parsing_and_conversion() {
+        assert_eq!("162,817,812".parse(), Ok(Point3::new(162, 817, 812)));
+        assert_eq!(" -1, 2 ".parse(), Ok(Point2::new(-1, 2)));
+        assert!("1,2".parse::<Point3>().is_err());
+        assert!("1,x".parse::<Point2>().is_err());
+        assert_eq!(Point2::new(3, 4).to_string(), "3,4");
+        assert_eq!(<(i64, i64)>::from(Point2::from((5, 6))), (5, 6));
+        assert_eq!(Point::from([1, 2, 3, 4])[3], 4);
+    }
+
+    #[test]
+    fn rotations_and_neighbours() {
+        let p = Point2::new(2, 1);
+        assert_eq!(p.rotate_left(), Point2::new(-1, 2));
+        assert_eq!(p.rotate_right().rotate_left(), p);
+        assert_eq!(p.rotate_about(&Point2::new(1, 1), 1), Point2::new(1, 2));
+        assert_eq!(p.rotate_about(&Point2::new(1, 1), -1), Point2::new(1, 0));
+
+        let q = Point3::new(1, 2, 3);
+        assert_eq!(q.rotate_x().rotate_x().rotate_x().rotate_x(), q);
+        assert_eq!(Point3::new(1, 0, 0).rotate_z(), Point3::new(0, 1, 0));
+        assert_eq!(Point3::new(0, 1, 0).rotate_x(), Point3::new(0, 0, 1));
+        assert_eq!(Point3::new(0, 0, 1).rotate_y(), Point3::new(1, 0, 0));
+
+        assert_eq!(p.orthogonal_neighbours().count(), 4);
+        assert_eq!(p.neighbours().count(), 8);
+        assert_eq!(Point::<4>::zero().neighbours().count(), 80);
+    }
+}
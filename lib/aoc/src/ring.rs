@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hash, Hasher};
+use std::iter::FromIterator;
+
+// Nodes are numbered in the order they were pushed and keep their id for the life of the ring
+pub type NodeId = usize;
+
+// A run of nodes cut out of the ring, still linked to each other so it can be spliced back in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Run {
+    pub first: NodeId,
+    pub last: NodeId,
+    pub len: usize,
+}
+
+// The multiply-rotate hash used by rustc, much quicker than the default for the small keys
+// rings are looked up by
+#[derive(Default)]
+struct FxHasher {
+    hash: u64,
+}
+
+impl Hasher for FxHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        bytes.iter().for_each(|b| self.write_u64(*b as u64));
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ n).wrapping_mul(0x51_7c_c1_b7_27_22_0a_95);
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        self.write_u64(n as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+type Index<T> = HashMap<T, NodeId, BuildHasherDefault<FxHasher>>;
+
+#[derive(Clone, Debug)]
+struct Node<T> {
+    value: T,
+    next: NodeId,
+    prev: NodeId,
+}
+
+// A circular doubly linked list stored in vectors, so nodes can be relinked in O(1) without
+// unsafe code or unstable cursors
+#[derive(Clone, Debug)]
+pub struct Ring<T> {
+    nodes: Vec<Node<T>>,
+    index: Index<T>,
+    len: usize,
+}
+
+impl<T> Default for Ring<T> {
+    fn default() -> Ring<T> {
+        Ring {
+            nodes: Vec::new(),
+            index: Index::default(),
+            len: 0,
+        }
+    }
+}
+
+impl<T: Clone + Eq + Hash> Ring<T> {
+    pub fn new() -> Ring<T> {
+        Ring::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Ring<T> {
+        Ring {
+            nodes: Vec::with_capacity(capacity),
+            index: Index::with_capacity_and_hasher(capacity, Default::default()),
+            len: 0,
+        }
+    }
+
+    fn allocate(&mut self, value: T) -> NodeId {
+        let id = self.nodes.len();
+        self.index.insert(value.clone(), id);
+        self.nodes.push(Node {
+            value,
+            next: id,
+            prev: id,
+        });
+        id
+    }
+
+    // Adds a value just before node 0, which is the end of the ring when read from node 0
+    pub fn push(&mut self, value: T) -> NodeId {
+        let id = self.allocate(value);
+        if id == 0 {
+            self.len = 1;
+        } else {
+            let last = self.nodes[0].prev;
+            self.link_after(last, Run::single(id));
+        }
+        id
+    }
+
+    pub fn insert_after(&mut self, id: NodeId, value: T) -> NodeId {
+        let new = self.allocate(value);
+        self.link_after(id, Run::single(new));
+        new
+    }
+
+    // Number of nodes currently in the ring, not counting any cut out
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn value(&self, id: NodeId) -> &T {
+        &self.nodes[id].value
+    }
+
+    pub fn next(&self, id: NodeId) -> NodeId {
+        self.nodes[id].next
+    }
+
+    pub fn prev(&self, id: NodeId) -> NodeId {
+        self.nodes[id].prev
+    }
+
+    // A node holding the value; if several do, the most recently added
+    pub fn find(&self, value: &T) -> Option<NodeId> {
+        self.index.get(value).copied()
+    }
+
+    // Follows `offset` links forwards, or backwards if negative
+    pub fn step(&self, mut id: NodeId, offset: i64) -> NodeId {
+        for _ in 0..offset.unsigned_abs() {
+            id = if offset < 0 {
+                self.nodes[id].prev
+            } else {
+                self.nodes[id].next
+            };
+        }
+        id
+    }
+
+    // Cuts out the `count` nodes following `id`
+    pub fn remove_after(&mut self, id: NodeId, count: usize) -> Run {
+        assert!(count > 0 && count < self.len, "Cannot cut {} nodes", count);
+        let first = self.nodes[id].next;
+        let last = self.step(id, count as i64);
+        let after = self.nodes[last].next;
+        self.nodes[id].next = after;
+        self.nodes[after].prev = id;
+        self.len -= count;
+        Run {
+            first,
+            last,
+            len: count,
+        }
+    }
+
+    pub fn remove(&mut self, id: NodeId) -> Run {
+        self.remove_after(self.nodes[id].prev, 1)
+    }
+
+    // Puts a run that was cut out back into the ring straight after `id`
+    pub fn splice_after(&mut self, id: NodeId, run: Run) {
+        self.link_after(id, run);
+    }
+
+    fn link_after(&mut self, id: NodeId, run: Run) {
+        let after = self.nodes[id].next;
+        self.nodes[id].next = run.first;
+        self.nodes[run.first].prev = id;
+        self.nodes[run.last].next = after;
+        self.nodes[after].prev = run.last;
+        self.len += run.len;
+    }
+
+    // Moves a node `offset` places along the ring, where moving past every other node once brings
+    // it back to where it started. Unlike the other edits this walks to the new place, whichever
+    // way round is shorter, so it costs up to half the length of the ring.
+    pub fn move_by(&mut self, id: NodeId, offset: i64) {
+        if self.len < 2 {
+            return;
+        }
+        let others = self.len as i64 - 1;
+        let mut steps = offset.rem_euclid(others);
+        if steps == 0 {
+            return;
+        }
+        if steps > others / 2 {
+            steps -= others;
+        }
+        let before = self.nodes[id].prev;
+        let run = self.remove(id);
+        let target = self.step(before, steps);
+        self.link_after(target, run);
+    }
+
+    // The values once round the ring starting from `id`
+    pub fn iter_from(&self, id: NodeId) -> impl Iterator<Item = &T> + '_ {
+        let mut current = id;
+        (0..self.len).map(move |_| {
+            let value = &self.nodes[current].value;
+            current = self.nodes[current].next;
+            value
+        })
+    }
+}
+
+impl Run {
+    pub fn single(id: NodeId) -> Run {
+        Run {
+            first: id,
+            last: id,
+            len: 1,
+        }
+    }
+}
+
+impl<T: Clone + Eq + Hash> FromIterator<T> for Ring<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut ring = Ring::new();
+        iter.into_iter().for_each(|value| {
+            ring.push(value);
+        });
+        ring
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Lcg;
+    use std::cmp::Ordering;
+
+    fn contents(ring: &Ring<i64>, value: i64) -> Vec<i64> {
+        ring.iter_from(ring.find(&value).unwrap())
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn cutting_and_splicing() {
+        let mut ring: Ring<i64> = (1..=6).collect();
+        assert_eq!(contents(&ring, 1), vec![1, 2, 3, 4, 5, 6]);
+        let run = ring.remove_after(0, 3);
+        assert_eq!((ring.len(), *ring.value(run.first)), (3, 2));
+        assert_eq!(contents(&ring, 5), vec![5, 6, 1]);
+        ring.splice_after(ring.find(&6).unwrap(), run);
+        assert_eq!(contents(&ring, 1), vec![1, 5, 6, 2, 3, 4]);
+        let seven = ring.insert_after(0, 7);
+        assert_eq!(ring.step(seven, 2), ring.find(&6).unwrap());
+        assert_eq!(ring.step(seven, -2), ring.find(&4).unwrap());
+        assert_eq!(contents(&ring, 1), vec![1, 7, 5, 6, 2, 3, 4]);
+    }
+
+    #[test]
+    fn mixing() {
+        let numbers = [1, 2, -3, 3, -2, 0, 4];
+        let mut ring: Ring<i64> = numbers.iter().cloned().collect();
+        for (id, n) in numbers.iter().enumerate() {
+            ring.move_by(id, *n);
+        }
+        assert_eq!(contents(&ring, 0), vec![0, 3, -2, 1, 2, -3, 4]);
+        ring.move_by(0, 6 * 1000);
+        assert_eq!(contents(&ring, 0), vec![0, 3, -2, 1, 2, -3, 4]);
+
+        // Against moving the values around a vector, with offsets either way round the ring
+        let mut random = Lcg::new(3);
+        let numbers: Vec<i64> = (0..20)
+            .map(|i| i * 100 + (random.next_u64() >> 40) as i64 % 100 - 50)
+            .collect();
+        let mut ring: Ring<i64> = numbers.iter().cloned().collect();
+        let mut order: Vec<usize> = (0..numbers.len()).collect();
+        for (id, n) in numbers.iter().enumerate() {
+            ring.move_by(id, *n);
+            let from = order.iter().position(|i| *i == id).unwrap();
+            order.remove(from);
+            let to = (from as i64 + n).rem_euclid(order.len() as i64) as usize;
+            order.insert(to, id);
+        }
+        let first = numbers[order[0]];
+        let expected: Vec<i64> = order.iter().map(|i| numbers[*i]).collect();
+        assert_eq!(contents(&ring, first), expected);
+
+        let empty: Ring<Ordering> = Ring::default();
+        assert!(empty.is_empty());
+    }
+}
@@ -0,0 +1,318 @@
+use crate::point::Point3;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+// A proper rotation of the integer lattice, stored as a matrix acting on column vectors
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rotation {
+    m: [[i64; 3]; 3],
+}
+
+impl Rotation {
+    pub fn identity() -> Rotation {
+        Rotation {
+            m: [[1, 0, 0], [0, 1, 0], [0, 0, 1]],
+        }
+    }
+
+    // Right-handed quarter turns about each axis, matching Point3::rotate_x and friends
+    pub fn about_x() -> Rotation {
+        Rotation {
+            m: [[1, 0, 0], [0, 0, -1], [0, 1, 0]],
+        }
+    }
+
+    pub fn about_y() -> Rotation {
+        Rotation {
+            m: [[0, 0, 1], [0, 1, 0], [-1, 0, 0]],
+        }
+    }
+
+    pub fn about_z() -> Rotation {
+        Rotation {
+            m: [[0, -1, 0], [1, 0, 0], [0, 0, 1]],
+        }
+    }
+
+    // The 24 ways to turn a cube, starting with the identity
+    pub fn all() -> Vec<Rotation> {
+        let mut seen = vec![Rotation::identity()];
+        let mut queue = VecDeque::from(vec![Rotation::identity()]);
+        while let Some(r) = queue.pop_front() {
+            for turn in [Rotation::about_x(), Rotation::about_y()].iter() {
+                let next = r.then(turn);
+                if !seen.contains(&next) {
+                    seen.push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+        seen
+    }
+
+    pub fn apply(&self, p: &Point3) -> Point3 {
+        let row = |i: usize| self.m[i][0] * p.x() + self.m[i][1] * p.y() + self.m[i][2] * p.z();
+        Point3::new(row(0), row(1), row(2))
+    }
+
+    // Applies self first, then other
+    pub fn then(&self, other: &Rotation) -> Rotation {
+        let mut m = [[0; 3]; 3];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = (0..3).map(|k| other.m[i][k] * self.m[k][j]).sum();
+            }
+        }
+        Rotation { m }
+    }
+
+    // Rotation matrices are orthogonal, so this is just the transpose
+    pub fn inverse(&self) -> Rotation {
+        let mut m = [[0; 3]; 3];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = self.m[j][i];
+            }
+        }
+        Rotation { m }
+    }
+}
+
+// A rotation followed by a translation, taking points from one frame into another
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Pose {
+    pub rotation: Rotation,
+    pub translation: Point3,
+}
+
+impl Pose {
+    pub fn identity() -> Pose {
+        Pose {
+            rotation: Rotation::identity(),
+            translation: Point3::zero(),
+        }
+    }
+
+    pub fn apply(&self, p: &Point3) -> Point3 {
+        self.rotation.apply(p) + self.translation
+    }
+
+    // Applies self first, then other
+    pub fn then(&self, other: &Pose) -> Pose {
+        Pose {
+            rotation: self.rotation.then(&other.rotation),
+            translation: other.apply(&self.translation),
+        }
+    }
+
+    pub fn inverse(&self) -> Pose {
+        let rotation = self.rotation.inverse();
+        Pose {
+            rotation,
+            translation: -rotation.apply(&self.translation),
+        }
+    }
+}
+
+// A set of points with the squared distance between every pair indexed. Distances don't change
+// under rotation or translation, so they show which clouds might overlap and which points
+// might correspond.
+#[derive(Clone, Debug)]
+pub struct Cloud {
+    points: Vec<Point3>,
+    present: HashSet<Point3>,
+    distances: HashMap<i64, Vec<(usize, usize)>>,
+}
+
+impl Cloud {
+    pub fn new(points: Vec<Point3>) -> Cloud {
+        let mut distances: HashMap<i64, Vec<(usize, usize)>> = HashMap::new();
+        for i in 0..points.len() {
+            for j in i + 1..points.len() {
+                let d = points[i].euclidean_squared(&points[j]);
+                distances.entry(d).or_default().push((i, j));
+            }
+        }
+        Cloud {
+            present: points.iter().copied().collect(),
+            points,
+            distances,
+        }
+    }
+
+    pub fn points(&self) -> &[Point3] {
+        &self.points
+    }
+
+    // How many pairwise distances appear in both clouds, counting repeats
+    pub fn shared_distances(&self, other: &Cloud) -> usize {
+        self.distances
+            .iter()
+            .filter_map(|(d, pairs)| other.distances.get(d).map(|o| pairs.len().min(o.len())))
+            .sum()
+    }
+
+    // The pose taking `other`'s points into this cloud's frame such that at least `min_overlap`
+    // of them land on points of this cloud
+    pub fn register(&self, other: &Cloud, min_overlap: usize) -> Option<Pose> {
+        if self.shared_distances(other) < min_overlap * min_overlap.saturating_sub(1) / 2 {
+            return None;
+        }
+        let rotations = Rotation::all();
+        let mut distances: Vec<&i64> = self.distances.keys().collect();
+        distances.sort_unstable();
+        for d in distances {
+            let theirs = match other.distances.get(d) {
+                Some(pairs) => pairs,
+                None => continue,
+            };
+            for (a, b) in self.distances[d].iter() {
+                for (c, e) in theirs.iter() {
+                    for (p, q) in [(c, e), (e, c)].iter() {
+                        for rotation in rotations.iter() {
+                            let translation = self.points[*a] - rotation.apply(&other.points[**p]);
+                            let pose = Pose {
+                                rotation: *rotation,
+                                translation,
+                            };
+                            if pose.apply(&other.points[**q]) != self.points[*b] {
+                                continue;
+                            }
+                            let overlap = other
+                                .points
+                                .iter()
+                                .filter(|x| self.present.contains(&pose.apply(x)))
+                                .count();
+                            if overlap >= min_overlap {
+                                return Some(pose);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Map {
+    // Takes each cloud into the first cloud's frame, so its translation is where that cloud's
+    // origin lies
+    pub poses: Vec<Pose>,
+    pub points: HashSet<Point3>,
+}
+
+// Lines every cloud up with the first by registering them against each other in turn
+pub fn build_map(clouds: &[Cloud], min_overlap: usize) -> Result<Map, String> {
+    let mut poses: Vec<Option<Pose>> = vec![None; clouds.len()];
+    let mut queue = VecDeque::new();
+    if !clouds.is_empty() {
+        poses[0] = Some(Pose::identity());
+        queue.push_back(0);
+    }
+    while let Some(i) = queue.pop_front() {
+        let placed = poses[i].unwrap();
+        for j in 0..clouds.len() {
+            if poses[j].is_some() {
+                continue;
+            }
+            if let Some(pose) = clouds[i].register(&clouds[j], min_overlap) {
+                poses[j] = Some(pose.then(&placed));
+                queue.push_back(j);
+            }
+        }
+    }
+
+    let unplaced: Vec<usize> = (0..clouds.len()).filter(|i| poses[*i].is_none()).collect();
+    if !unplaced.is_empty() {
+        return Err(format!(
+            "Clouds {:?} don't overlap enough with the rest",
+            unplaced
+        ));
+    }
+    let poses: Vec<Pose> = poses.into_iter().map(Option::unwrap).collect();
+    let points = clouds
+        .iter()
+        .zip(poses.iter())
+        .flat_map(|(cloud, pose)| cloud.points.iter().map(move |p| pose.apply(p)))
+        .collect();
+    Ok(Map { poses, points })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Lcg;
+
+    #[test]
+    fn rotation_group() {
+        let all = Rotation::all();
+        assert_eq!(all.len(), 24);
+        assert_eq!(all[0], Rotation::identity());
+        for a in all.iter() {
+            assert_eq!(a.then(&a.inverse()), Rotation::identity());
+            for b in all.iter() {
+                assert!(all.contains(&a.then(b)));
+            }
+        }
+
+        let p = Point3::new(1, 2, 3);
+        assert_eq!(Rotation::about_x().apply(&p), p.rotate_x());
+        assert_eq!(Rotation::about_y().apply(&p), p.rotate_y());
+        assert_eq!(Rotation::about_z().apply(&p), p.rotate_z());
+        assert_eq!(
+            Rotation::about_x().then(&Rotation::about_z()).apply(&p),
+            p.rotate_x().rotate_z()
+        );
+    }
+
+    #[test]
+    fn poses() {
+        let pose = Pose {
+            rotation: Rotation::about_y().then(&Rotation::about_z()),
+            translation: Point3::new(5, -3, 2),
+        };
+        let p = Point3::new(1, 2, 3);
+        assert_eq!(pose.inverse().apply(&pose.apply(&p)), p);
+        assert_eq!(pose.then(&pose.inverse()), Pose::identity());
+        assert_eq!(pose.then(&pose).apply(&p), pose.apply(&pose.apply(&p)));
+    }
+
+    #[test]
+    fn registration() {
+        let mut random = Lcg::new(42);
+        let mut next = || (random.next_u64() >> 33) as i64 % 1000;
+        let world: Vec<Point3> = (0..40)
+            .map(|_| Point3::new(next(), next(), next()))
+            .collect();
+        let rotations = Rotation::all();
+        let truth: Vec<Pose> = (0..3)
+            .map(|i| Pose {
+                rotation: rotations[i * 7],
+                translation: Point3::new(100 * i as i64, -50, 25 * i as i64),
+            })
+            .collect();
+        // Each cloud sees an overlapping window of the world in its own frame
+        let clouds: Vec<Cloud> = truth
+            .iter()
+            .enumerate()
+            .map(|(i, pose)| {
+                let inverse = pose.inverse();
+                Cloud::new(
+                    world[i * 10..i * 10 + 20]
+                        .iter()
+                        .map(|p| inverse.apply(p))
+                        .collect(),
+                )
+            })
+            .collect();
+
+        let map = build_map(&clouds, 10).unwrap();
+        assert_eq!(map.points.len(), 40);
+        let relative = truth[0].inverse();
+        for (pose, expected) in map.poses.iter().zip(truth.iter()) {
+            assert_eq!(*pose, expected.then(&relative));
+        }
+        assert!(build_map(&clouds, 11).is_err());
+    }
+}
@@ -0,0 +1,466 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Path<S, C> {
+    pub states: Vec<S>,
+    pub cost: C,
+}
+
+type Successors<'a, S, C> = Box<dyn FnMut(&S) -> Vec<(S, C)> + 'a>;
+type Heuristic<'a, S, C> = Box<dyn FnMut(&S) -> C + 'a>;
+type Prune<'a, S, C> = Box<dyn FnMut(&S, C) -> bool + 'a>;
+
+// Best-first search over weighted edges. Without a heuristic this is Dijkstra, with one it is
+// A*; the heuristic must never overestimate the remaining cost. Pruning drops any state the
+// closure rejects, given the cost it would be reached at, before it is queued.
+pub struct Search<'a, S, C> {
+    successors: Successors<'a, S, C>,
+    heuristic: Option<Heuristic<'a, S, C>>,
+    prune: Option<Prune<'a, S, C>>,
+}
+
+struct Explored<S, C> {
+    states: Vec<S>,
+    ids: HashMap<S, usize>,
+    costs: Vec<C>,
+    parents: Vec<Vec<usize>>,
+}
+
+impl<S: Clone + Eq + Hash, C> Explored<S, C> {
+    fn new() -> Explored<S, C> {
+        Explored {
+            states: Vec::new(),
+            ids: HashMap::new(),
+            costs: Vec::new(),
+            parents: Vec::new(),
+        }
+    }
+
+    fn id(&mut self, state: &S, cost: C) -> (usize, bool) {
+        if let Some(id) = self.ids.get(state) {
+            return (*id, false);
+        }
+        let id = self.states.len();
+        self.states.push(state.clone());
+        self.ids.insert(state.clone(), id);
+        self.costs.push(cost);
+        self.parents.push(Vec::new());
+        (id, true)
+    }
+
+    fn path(&self, mut id: usize) -> Vec<S> {
+        let mut path = vec![self.states[id].clone()];
+        while let Some(parent) = self.parents[id].first() {
+            id = *parent;
+            path.push(self.states[id].clone());
+        }
+        path.reverse();
+        path
+    }
+
+    fn all_paths(&self, id: usize) -> Vec<Vec<S>> {
+        if self.parents[id].is_empty() {
+            return vec![vec![self.states[id].clone()]];
+        }
+        self.parents[id]
+            .iter()
+            .flat_map(|parent| self.all_paths(*parent))
+            .map(|mut path| {
+                path.push(self.states[id].clone());
+                path
+            })
+            .collect()
+    }
+}
+
+impl<'a, S, C> Search<'a, S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+{
+    pub fn new(successors: impl FnMut(&S) -> Vec<(S, C)> + 'a) -> Search<'a, S, C> {
+        Search {
+            successors: Box::new(successors),
+            heuristic: None,
+            prune: None,
+        }
+    }
+
+    pub fn with_heuristic(mut self, heuristic: impl FnMut(&S) -> C + 'a) -> Search<'a, S, C> {
+        self.heuristic = Some(Box::new(heuristic));
+        self
+    }
+
+    pub fn with_pruning(mut self, prune: impl FnMut(&S, C) -> bool + 'a) -> Search<'a, S, C> {
+        self.prune = Some(Box::new(prune));
+        self
+    }
+
+    fn estimate(&mut self, state: &S) -> C {
+        match &mut self.heuristic {
+            Some(heuristic) => heuristic(state),
+            None => C::default(),
+        }
+    }
+
+    // Pops states in order of estimated total cost until one passes the goal test or, without
+    // `stop_at_goal`, until every goal at the best cost has been found.
+    fn run(
+        &mut self,
+        starts: impl IntoIterator<Item = S>,
+        mut is_goal: impl FnMut(&S) -> bool,
+        record_ties: bool,
+        stop_at_goal: bool,
+    ) -> (Explored<S, C>, Vec<usize>) {
+        let mut explored = Explored::new();
+        let mut queue = BinaryHeap::new();
+        for start in starts {
+            let (id, new) = explored.id(&start, C::default());
+            if new {
+                let estimate = self.estimate(&start);
+                queue.push(Reverse((estimate, C::default(), id)));
+            }
+        }
+
+        let mut goals = Vec::new();
+        let mut goal_cost = None;
+        while let Some(Reverse((estimate, cost, id))) = queue.pop() {
+            if cost > explored.costs[id] {
+                continue;
+            }
+            if let Some(best) = goal_cost {
+                if estimate > best {
+                    break;
+                }
+            }
+            let state = explored.states[id].clone();
+            if is_goal(&state) {
+                goals.push(id);
+                goal_cost = Some(cost);
+                if stop_at_goal {
+                    break;
+                }
+                continue;
+            }
+            for (next, step) in (self.successors)(&state) {
+                let next_cost = cost + step;
+                if let Some(prune) = &mut self.prune {
+                    if prune(&next, next_cost) {
+                        continue;
+                    }
+                }
+                let (next_id, new) = explored.id(&next, next_cost);
+                if new || next_cost < explored.costs[next_id] {
+                    explored.costs[next_id] = next_cost;
+                    explored.parents[next_id] = vec![id];
+                    let estimate = next_cost + self.estimate(&next);
+                    queue.push(Reverse((estimate, next_cost, next_id)));
+                } else if record_ties
+                    && next_cost == explored.costs[next_id]
+                    && !explored.parents[next_id].contains(&id)
+                {
+                    explored.parents[next_id].push(id);
+                }
+            }
+        }
+        (explored, goals)
+    }
+
+    pub fn shortest_path(
+        &mut self,
+        starts: impl IntoIterator<Item = S>,
+        is_goal: impl FnMut(&S) -> bool,
+    ) -> Option<Path<S, C>> {
+        let (explored, goals) = self.run(starts, is_goal, false, true);
+        goals.first().map(|goal| Path {
+            states: explored.path(*goal),
+            cost: explored.costs[*goal],
+        })
+    }
+
+    // Every path sharing the minimum cost, to any goal state reachable at that cost
+    pub fn all_shortest_paths(
+        &mut self,
+        starts: impl IntoIterator<Item = S>,
+        is_goal: impl FnMut(&S) -> bool,
+    ) -> Option<(Vec<Vec<S>>, C)> {
+        let (explored, goals) = self.run(starts, is_goal, true, false);
+        let cost = explored.costs[*goals.first()?];
+        let paths = goals
+            .iter()
+            .flat_map(|goal| explored.all_paths(*goal))
+            .collect();
+        Some((paths, cost))
+    }
+
+    // Minimum cost to every reachable state
+    pub fn costs(&mut self, starts: impl IntoIterator<Item = S>) -> HashMap<S, C> {
+        let (explored, _) = self.run(starts, |_| false, false, false);
+        explored.states.into_iter().zip(explored.costs).collect()
+    }
+}
+
+pub fn dijkstra<S, C>(
+    start: S,
+    successors: impl FnMut(&S) -> Vec<(S, C)>,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+{
+    Search::new(successors).shortest_path(vec![start], is_goal)
+}
+
+pub fn astar<S, C>(
+    start: S,
+    successors: impl FnMut(&S) -> Vec<(S, C)>,
+    heuristic: impl FnMut(&S) -> C,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+{
+    Search::new(successors)
+        .with_heuristic(heuristic)
+        .shortest_path(vec![start], is_goal)
+}
+
+// Unit cost search. The goal test is applied as states are discovered rather than when they are
+// expanded, which is safe when every step costs the same.
+pub fn bfs<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, usize>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut parents: HashMap<S, Option<S>> = HashMap::new();
+    let mut queue = VecDeque::new();
+    let path = |parents: &HashMap<S, Option<S>>, end: S| {
+        let mut states = vec![end];
+        while let Some(Some(parent)) = parents.get(states.last().unwrap()) {
+            states.push(parent.clone());
+        }
+        states.reverse();
+        let cost = states.len() - 1;
+        Path { states, cost }
+    };
+
+    for start in starts {
+        if parents.contains_key(&start) {
+            continue;
+        }
+        parents.insert(start.clone(), None);
+        if is_goal(&start) {
+            return Some(path(&parents, start));
+        }
+        queue.push_back(start);
+    }
+
+    while let Some(state) = queue.pop_front() {
+        for next in successors(&state) {
+            if parents.contains_key(&next) {
+                continue;
+            }
+            parents.insert(next.clone(), Some(state.clone()));
+            if is_goal(&next) {
+                return Some(path(&parents, next));
+            }
+            queue.push_back(next);
+        }
+    }
+    None
+}
+
+// Unit cost search from both ends at once, expanding the smaller frontier one layer at a time.
+// `predecessors` gives the states with an edge into the given one, so is the same as
+// `successors` for undirected graphs.
+pub fn bidirectional_bfs<S, I, J>(
+    start: S,
+    goal: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut predecessors: impl FnMut(&S) -> J,
+) -> Option<Path<S, usize>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+    J: IntoIterator<Item = S>,
+{
+    let mut forward: HashMap<S, Option<S>> = HashMap::new();
+    let mut backward: HashMap<S, Option<S>> = HashMap::new();
+    forward.insert(start.clone(), None);
+    backward.insert(goal.clone(), None);
+    let mut forward_frontier = vec![start.clone()];
+    let mut backward_frontier = vec![goal.clone()];
+
+    let join = |meeting: S, forward: &HashMap<S, Option<S>>, backward: &HashMap<S, Option<S>>| {
+        let mut states = vec![meeting.clone()];
+        while let Some(Some(parent)) = forward.get(states.last().unwrap()) {
+            states.push(parent.clone());
+        }
+        states.reverse();
+        let mut current = meeting;
+        while let Some(Some(next)) = backward.get(&current) {
+            states.push(next.clone());
+            current = next.clone();
+        }
+        let cost = states.len() - 1;
+        Path { states, cost }
+    };
+
+    if start == goal {
+        return Some(join(start, &forward, &backward));
+    }
+
+    while !forward_frontier.is_empty() && !backward_frontier.is_empty() {
+        let expand_forward = forward_frontier.len() <= backward_frontier.len();
+        let (frontier, seen, other) = if expand_forward {
+            (&mut forward_frontier, &mut forward, &backward)
+        } else {
+            (&mut backward_frontier, &mut backward, &forward)
+        };
+        let mut next_frontier = Vec::new();
+        let mut meeting = None;
+        for state in frontier.drain(..) {
+            let neighbours: Vec<S> = if expand_forward {
+                successors(&state).into_iter().collect()
+            } else {
+                predecessors(&state).into_iter().collect()
+            };
+            for next in neighbours {
+                if seen.contains_key(&next) {
+                    continue;
+                }
+                seen.insert(next.clone(), Some(state.clone()));
+                if other.contains_key(&next) {
+                    meeting = Some(next.clone());
+                    break;
+                }
+                next_frontier.push(next);
+            }
+            if meeting.is_some() {
+                break;
+            }
+        }
+        if let Some(meeting) = meeting {
+            return Some(join(meeting, &forward, &backward));
+        }
+        *frontier = next_frontier;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid_successors(walls: &'static [&'static str]) -> impl Fn(&(i64, i64)) -> Vec<(i64, i64)> {
+        move |(x, y)| {
+            [(0, 1), (1, 0), (0, -1), (-1, 0)]
+                .iter()
+                .map(|(dx, dy)| (x + dx, y + dy))
+                .filter(|(x, y)| {
+                    *x >= 0
+                        && *y >= 0
+                        && walls
+                            .get(*y as usize)
+                            .and_then(|row| row.as_bytes().get(*x as usize))
+                            == Some(&b'.')
+                })
+                .collect()
+        }
+    }
+
+    const MAZE: [&str; 5] = ["....#", ".##.#", ".#...", ".#.#.", "...#."];
+
+    #[test]
+    fn unweighted() {
+        let successors = grid_successors(&MAZE);
+        let path = bfs(vec![(0, 0)], &successors, |p| *p == (4, 4)).unwrap();
+        assert_eq!(path.cost, 8);
+        assert_eq!(path.states.first(), Some(&(0, 0)));
+        assert_eq!(path.states.last(), Some(&(4, 4)));
+        assert!(bfs(vec![(0, 0)], &successors, |p| *p == (4, 0)).is_none());
+
+        let both = bidirectional_bfs((0, 0), (4, 4), &successors, &successors).unwrap();
+        assert_eq!(both.cost, 8);
+        assert_eq!(both.states.len(), 9);
+        assert!(both
+            .states
+            .windows(2)
+            .all(|pair| successors(&pair[0]).contains(&pair[1])));
+    }
+
+    #[test]
+    fn weighted() {
+        let graph: HashMap<char, Vec<(char, u32)>> = [
+            ('a', vec![('b', 7), ('c', 9), ('f', 14)]),
+            ('b', vec![('c', 10), ('d', 15)]),
+            ('c', vec![('d', 11), ('f', 2)]),
+            ('d', vec![('e', 6)]),
+            ('f', vec![('e', 9)]),
+        ]
+        .iter()
+        .cloned()
+        .collect();
+        let successors = |c: &char| graph.get(c).cloned().unwrap_or_default();
+
+        let path = dijkstra('a', successors, |c| *c == 'e').unwrap();
+        assert_eq!(
+            path,
+            Path {
+                states: vec!['a', 'c', 'f', 'e'],
+                cost: 20
+            }
+        );
+
+        let (paths, cost) = Search::new(successors)
+            .all_shortest_paths(vec!['a'], |c| *c == 'd')
+            .unwrap();
+        assert_eq!(cost, 20);
+        assert_eq!(paths, vec![vec!['a', 'c', 'd']]);
+
+        let pruned = Search::new(successors)
+            .with_pruning(|c, _| *c == 'f')
+            .shortest_path(vec!['a'], |c| *c == 'e')
+            .unwrap();
+        assert_eq!(pruned.cost, 26);
+
+        let costs = Search::new(successors).costs(vec!['a']);
+        assert_eq!(costs[&'f'], 11);
+        assert_eq!(costs.len(), 6);
+    }
+
+    #[test]
+    fn heuristic_and_ties() {
+        let successors = |p: &(i64, i64)| {
+            let (x, y) = *p;
+            vec![((x + 1, y), 1), ((x, y + 1), 1)]
+                .into_iter()
+                .filter(|((x, y), _)| *x <= 2 && *y <= 2)
+                .collect::<Vec<_>>()
+        };
+        let path = astar(
+            (0, 0),
+            successors,
+            |(x, y)| (2 - x) + (2 - y),
+            |p| *p == (2, 2),
+        )
+        .unwrap();
+        assert_eq!(path.cost, 4);
+
+        let (paths, cost) = Search::new(successors)
+            .with_heuristic(|(x, y)| (2 - x) + (2 - y))
+            .all_shortest_paths(vec![(0, 0)], |p| *p == (2, 2))
+            .unwrap();
+        assert_eq!(cost, 4);
+        assert_eq!(paths.len(), 6);
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+// Disjoint sets over the elements 0..n, with path compression and union by size
+#[derive(Clone, Debug)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    components: usize,
+}
+
+impl UnionFind {
+    pub fn new(n: usize) -> UnionFind {
+        UnionFind {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            components: n,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    // The representative of `x`'s component, pointing everything on the way straight at it
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut current = x;
+        while self.parent[current] != root {
+            current = std::mem::replace(&mut self.parent[current], root);
+        }
+        root
+    }
+
+    // Joins the components of `a` and `b`, returning whether they were separate
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.components -= 1;
+        true
+    }
+
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    // The number of elements in `x`'s component
+    pub fn size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.components
+    }
+
+    // Every component, in order of its smallest element
+    pub fn components(&mut self) -> Vec<Vec<usize>> {
+        let mut index: HashMap<usize, usize> = HashMap::new();
+        let mut result: Vec<Vec<usize>> = Vec::with_capacity(self.components);
+        for x in 0..self.len() {
+            let root = self.find(x);
+            let i = *index.entry(root).or_insert_with(|| {
+                result.push(Vec::new());
+                result.len() - 1
+            });
+            result[i].push(x);
+        }
+        result
+    }
+
+    // The size of every component, largest first
+    pub fn component_sizes(&self) -> Vec<usize> {
+        let mut sizes: Vec<usize> = (0..self.len())
+            .filter(|x| self.parent[*x] == *x)
+            .map(|x| self.size[x])
+            .collect();
+        sizes.sort_unstable_by(|a, b| b.cmp(a));
+        sizes
+    }
+}
+
+// The outcome of merging edges in weight order
+#[derive(Clone, Debug)]
+pub struct Spanning<W> {
+    // The edges that joined two components, in the order they were merged
+    pub edges: Vec<(usize, usize, W)>,
+    pub sets: UnionFind,
+}
+
+impl<W> Spanning<W> {
+    // The merge that left a single component, if there was one
+    pub fn joining_edge(&self) -> Option<&(usize, usize, W)> {
+        if self.sets.component_count() == 1 {
+            self.edges.last()
+        } else {
+            None
+        }
+    }
+
+    pub fn total_weight(&self) -> W
+    where
+        W: Copy + std::iter::Sum<W>,
+    {
+        self.edges.iter().map(|e| e.2).sum()
+    }
+}
+
+// Kruskal's algorithm over `nodes` elements, stopping as soon as everything is joined. Equal
+// weights are merged in the order they were given.
+pub fn kruskal<W: Ord>(
+    nodes: usize,
+    edges: impl IntoIterator<Item = (usize, usize, W)>,
+) -> Spanning<W> {
+    let mut edges: Vec<_> = edges.into_iter().collect();
+    edges.sort_by(|a, b| a.2.cmp(&b.2));
+    let mut sets = UnionFind::new(nodes);
+    let mut merged = Vec::new();
+    for (a, b, weight) in edges {
+        if sets.component_count() <= 1 {
+            break;
+        }
+        if sets.union(a, b) {
+            merged.push((a, b, weight));
+        }
+    }
+    Spanning {
+        edges: merged,
+        sets,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn components() {
+        let mut sets = UnionFind::new(7);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(2, 1));
+        assert!(sets.union(4, 5));
+        assert!(!sets.union(0, 2));
+        assert!(sets.connected(0, 2));
+        assert!(!sets.connected(0, 4));
+        assert_eq!(sets.size(2), 3);
+        assert_eq!(sets.component_count(), 4);
+        assert_eq!(sets.component_sizes(), vec![3, 2, 1, 1]);
+        assert_eq!(
+            sets.components(),
+            vec![vec![0, 1, 2], vec![3], vec![4, 5], vec![6]]
+        );
+    }
+
+    #[test]
+    fn spanning_tree() {
+        let edges = vec![
+            (0, 1, 7),
+            (0, 3, 5),
+            (1, 2, 8),
+            (1, 3, 9),
+            (1, 4, 7),
+            (2, 4, 5),
+            (3, 4, 15),
+            (3, 5, 6),
+            (4, 5, 8),
+            (4, 6, 9),
+            (5, 6, 11),
+        ];
+        let spanning = kruskal(7, edges);
+        assert_eq!(spanning.total_weight(), 39);
+        assert_eq!(spanning.edges.len(), 6);
+        assert_eq!(spanning.joining_edge(), Some(&(4, 6, 9)));
+
+        let forest = kruskal(4, vec![(0, 1, 1), (2, 3, 1)]);
+        assert_eq!(forest.joining_edge(), None);
+        assert_eq!(forest.sets.component_count(), 2);
+    }
+}